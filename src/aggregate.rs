@@ -1,15 +1,65 @@
-use crate::{crossdev, InodeFilter, Throttle, WalkOptions, WalkResult};
+use crate::traverse::{Traversal, Tree, TreeIndex};
+use crate::{
+    crossdev, get_entry_or_panic, ByteFormat, DirEntry, InodeFilter, Throttle, WalkOptions,
+    WalkResult,
+};
 use anyhow::Result;
 use filesize::PathExt;
 use owo_colors::{AnsiColors as Color, OwoColorize};
+use petgraph::Direction;
+use serde::Serialize;
 use std::time::Duration;
-use std::{io, path::Path};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt, io,
+    path::{Path, PathBuf},
+};
 
-/// Aggregate the given `paths` and write information about them to `out` in a human-readable format.
-/// If `compute_total` is set, it will write an additional line with the total size across all given `paths`.
+/// Specifies how the results of an aggregation are written
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// colored lines with formatted byte counts, one per path, meant to be read by humans.
+    #[default]
+    Human,
+    /// a single JSON document with exact byte counts, meant to be read by programs.
+    Json,
+}
+
+/// Aggregate the given `paths` and write information about them to `out` in the given `output_format`.
+/// If `compute_total` is set, it will write the total size across all given `paths` as well.
 /// If `sort_by_size_in_bytes` is set, we will sort all sizes (ascending) before outputting them.
 pub fn aggregate(
-    mut out: impl io::Write,
+    out: impl io::Write,
+    err: Option<impl io::Write>,
+    walk_options: WalkOptions,
+    output_format: OutputFormat,
+    compute_total: bool,
+    sort_by_size_in_bytes: bool,
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> Result<(WalkResult, Statistics)> {
+    match output_format {
+        OutputFormat::Human => aggregate_to(
+            HumanOutput::new(out, walk_options.byte_format, TreeStyle::default()),
+            err,
+            walk_options,
+            compute_total,
+            sort_by_size_in_bytes,
+            paths,
+        ),
+        OutputFormat::Json => aggregate_to(
+            JsonOutput::new(out),
+            err,
+            walk_options,
+            compute_total,
+            sort_by_size_in_bytes,
+            paths,
+        ),
+    }
+}
+
+fn aggregate_to(
+    mut out: impl Output,
     mut err: Option<impl io::Write>,
     walk_options: WalkOptions,
     compute_total: bool,
@@ -18,17 +68,15 @@ pub fn aggregate(
 ) -> Result<(WalkResult, Statistics)> {
     let mut res = WalkResult::default();
     let mut stats = Statistics {
-        smallest_file_in_bytes: u128::max_value(),
+        smallest_file_in_bytes: u128::MAX,
         ..Default::default()
     };
     let mut total = 0;
-    let mut num_roots = 0;
     let mut aggregates = Vec::new();
     let mut inodes = InodeFilter::default();
     let progress = Throttle::new(Duration::from_millis(100), Duration::from_secs(1).into());
 
     for path in paths.into_iter() {
-        num_roots += 1;
         let mut num_bytes = 0u128;
         let mut num_errors = 0u64;
         let device_id = match crossdev::init(path.as_ref()) {
@@ -36,7 +84,11 @@ pub fn aggregate(
             Err(_) => {
                 num_errors += 1;
                 res.num_errors += 1;
-                aggregates.push((path.as_ref().to_owned(), num_bytes, num_errors));
+                if sort_by_size_in_bytes {
+                    aggregates.push((path.as_ref().to_owned(), num_bytes, num_errors));
+                } else {
+                    out.add_path(path.as_ref(), 0, num_bytes, num_errors)?;
+                }
                 continue;
             }
         };
@@ -49,29 +101,14 @@ pub fn aggregate(
             });
             match entry {
                 Ok(entry) => {
-                    let file_size = match entry.client_state {
-                        Some(Ok(ref m))
-                            if !m.is_dir()
-                                && (walk_options.count_hard_links || inodes.add(m))
-                                && (walk_options.cross_filesystems
-                                    || crossdev::is_same_device(device_id, m)) =>
-                        {
-                            if walk_options.apparent_size {
-                                m.len()
-                            } else {
-                                entry.path().size_on_disk_fast(m).unwrap_or_else(|_| {
-                                    num_errors += 1;
-                                    0
-                                })
-                            }
-                        }
-                        Some(Ok(_)) => 0,
-                        Some(Err(_)) => {
-                            num_errors += 1;
-                            0
-                        }
-                        None => 0, // ignore directory
-                    } as u128;
+                    let file_size = size_of_file(
+                        &entry,
+                        &walk_options,
+                        &mut inodes,
+                        device_id,
+                        &mut num_errors,
+                    )
+                    .unwrap_or_default();
                     stats.largest_file_in_bytes = stats.largest_file_in_bytes.max(file_size);
                     stats.smallest_file_in_bytes = stats.smallest_file_in_bytes.min(file_size);
                     num_bytes += file_size;
@@ -87,14 +124,7 @@ pub fn aggregate(
         if sort_by_size_in_bytes {
             aggregates.push((path.as_ref().to_owned(), num_bytes, num_errors));
         } else {
-            output_colored_path(
-                &mut out,
-                &walk_options,
-                &path,
-                num_bytes,
-                num_errors,
-                path_color_of(&path),
-            )?;
+            out.add_path(path.as_ref(), 0, num_bytes, num_errors)?;
         }
         total += num_bytes;
         res.num_errors += num_errors;
@@ -107,28 +137,509 @@ pub fn aggregate(
     if sort_by_size_in_bytes {
         aggregates.sort_by_key(|&(_, num_bytes, _)| num_bytes);
         for (path, num_bytes, num_errors) in aggregates.into_iter() {
-            output_colored_path(
-                &mut out,
-                &walk_options,
-                &path,
+            out.add_path(&path, 0, num_bytes, num_errors)?;
+        }
+    }
+
+    if compute_total {
+        out.add_total(total, res.num_errors)?;
+    }
+    out.finish(&stats)?;
+    Ok((res, stats))
+}
+
+/// Return the size of the file at `entry` in bytes, or `None` if it doesn't contribute to the total
+/// as it is a directory, a hard link seen before, a file on another filesystem or couldn't be read.
+fn size_of_file(
+    entry: &DirEntry,
+    walk_options: &WalkOptions,
+    inodes: &mut InodeFilter,
+    device_id: u64,
+    num_errors: &mut u64,
+) -> Option<u128> {
+    match entry.client_state {
+        Some(Ok(ref m))
+            if !m.is_dir()
+                && (walk_options.count_hard_links || inodes.add(m))
+                && (walk_options.cross_filesystems || crossdev::is_same_device(device_id, m)) =>
+        {
+            let size = if walk_options.apparent_size {
+                Ok(m.len())
+            } else {
+                entry.path().size_on_disk_fast(m)
+            };
+            match size {
+                Ok(size) => Some(size as u128),
+                Err(_) => {
+                    *num_errors += 1;
+                    None
+                }
+            }
+        }
+        Some(Ok(_)) => None,
+        Some(Err(_)) => {
+            *num_errors += 1;
+            None
+        }
+        None => None, // ignore directory
+    }
+}
+
+/// Find the `num_files` largest files in the given `paths` and write them to `out` in the given `output_format`,
+/// sorted by size in bytes, ascending.
+pub fn top(
+    out: impl io::Write,
+    err: Option<impl io::Write>,
+    walk_options: WalkOptions,
+    output_format: OutputFormat,
+    num_files: usize,
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> Result<(WalkResult, Statistics)> {
+    match output_format {
+        OutputFormat::Human => top_to(
+            HumanOutput::new(out, walk_options.byte_format, TreeStyle::default()),
+            err,
+            walk_options,
+            num_files,
+            paths,
+        ),
+        OutputFormat::Json => top_to(JsonOutput::new(out), err, walk_options, num_files, paths),
+    }
+}
+
+fn top_to(
+    mut out: impl Output,
+    mut err: Option<impl io::Write>,
+    walk_options: WalkOptions,
+    num_files: usize,
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> Result<(WalkResult, Statistics)> {
+    let mut res = WalkResult::default();
+    let mut stats = Statistics {
+        smallest_file_in_bytes: u128::MAX,
+        ..Default::default()
+    };
+    // A min-heap, so the smallest of the largest files is the first to go when a larger one is found.
+    let mut largest = BinaryHeap::<Reverse<(u128, PathBuf)>>::with_capacity(num_files + 1);
+    let mut inodes = InodeFilter::default();
+    let progress = Throttle::new(Duration::from_millis(100), Duration::from_secs(1).into());
+
+    for path in paths.into_iter() {
+        let device_id = match crossdev::init(path.as_ref()) {
+            Ok(id) => id,
+            Err(_) => {
+                res.num_errors += 1;
+                continue;
+            }
+        };
+        for entry in walk_options.iter_from_path(path.as_ref(), device_id) {
+            stats.entries_traversed += 1;
+            progress.throttled(|| {
+                if let Some(err) = err.as_mut() {
+                    write!(err, "Enumerating {} entries\r", stats.entries_traversed).ok();
+                }
+            });
+            match entry {
+                Ok(entry) => {
+                    if entry.file_type.is_dir() {
+                        continue;
+                    }
+                    let file_size = match size_of_file(
+                        &entry,
+                        &walk_options,
+                        &mut inodes,
+                        device_id,
+                        &mut res.num_errors,
+                    ) {
+                        Some(file_size) => file_size,
+                        None => continue,
+                    };
+                    stats.largest_file_in_bytes = stats.largest_file_in_bytes.max(file_size);
+                    stats.smallest_file_in_bytes = stats.smallest_file_in_bytes.min(file_size);
+                    let is_among_largest = largest.len() < num_files
+                        || largest
+                            .peek()
+                            .is_some_and(|Reverse((smallest, _))| file_size > *smallest);
+                    if is_among_largest {
+                        largest.push(Reverse((file_size, entry.path())));
+                        if largest.len() > num_files {
+                            largest.pop();
+                        }
+                    }
+                }
+                Err(_) => res.num_errors += 1,
+            }
+        }
+
+        if let Some(err) = err.as_mut() {
+            write!(err, "\x1b[2K\r").ok();
+        }
+    }
+
+    if stats.smallest_file_in_bytes == u128::MAX {
+        stats.smallest_file_in_bytes = 0;
+    }
+    for Reverse((num_bytes, path)) in largest.into_sorted_vec().into_iter().rev() {
+        out.add_path(&path, 0, num_bytes, 0)?;
+    }
+    out.finish(&stats)?;
+    Ok((res, stats))
+}
+
+/// Specifies how directories below the input paths are presented in a tree report
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeStyle {
+    /// show the name of each directory below its parent, indented by its depth.
+    #[default]
+    Indented,
+    /// show the full path of each directory, after all of its children, similar to `du`.
+    Flat,
+}
+
+/// Configures which part of the traversal tree is reported by [`aggregate_tree()`].
+#[derive(Debug, Clone, Copy)]
+pub struct TreeOptions {
+    /// The amount of levels below each input path to show directories for. 0 shows only the input paths.
+    pub max_depth: usize,
+    pub style: TreeStyle,
+    /// If set, the total size across all input paths will be written as well.
+    pub compute_total: bool,
+    /// If set, input paths as well as directories of the same parent are sorted by size, ascending.
+    pub sort_by_size_in_bytes: bool,
+}
+
+/// Traverse the given `paths` and write the aggregated size of each of them as well as of all directories
+/// up to `options.max_depth` levels below them to `out`, in the given `output_format`.
+pub fn aggregate_tree(
+    out: impl io::Write,
+    mut err: Option<impl io::Write>,
+    walk_options: WalkOptions,
+    output_format: OutputFormat,
+    options: TreeOptions,
+    paths: Vec<PathBuf>,
+) -> Result<(WalkResult, Statistics)> {
+    let byte_format = walk_options.byte_format;
+    let traversal = Traversal::from_walk(walk_options, paths.clone(), |t| {
+        if let Some(err) = err.as_mut() {
+            write!(err, "Enumerating {} entries\r", t.entries_traversed).ok();
+        }
+        Ok(false)
+    })?
+    .expect("no abort as we never ask for it");
+    if let Some(err) = err.as_mut() {
+        write!(err, "\x1b[2K\r").ok();
+    }
+
+    match output_format {
+        OutputFormat::Human => output_tree(
+            HumanOutput::new(out, byte_format, options.style),
+            &traversal,
+            options,
+            paths,
+        ),
+        OutputFormat::Json => output_tree(JsonOutput::new(out), &traversal, options, paths),
+    }
+}
+
+fn output_tree(
+    mut out: impl Output,
+    traversal: &Traversal,
+    options: TreeOptions,
+    paths: Vec<PathBuf>,
+) -> Result<(WalkResult, Statistics)> {
+    struct Entry {
+        path: PathBuf,
+        depth: usize,
+        num_bytes: u128,
+        num_errors: u64,
+    }
+
+    fn sorted_children(tree: &Tree, idx: TreeIndex, sort_by_size: bool) -> Vec<TreeIndex> {
+        let mut children: Vec<_> = tree.neighbors_directed(idx, Direction::Outgoing).collect();
+        // neighbors are returned in reverse order of insertion, which is the traversal order
+        children.reverse();
+        if sort_by_size {
+            children.sort_by_key(|idx| get_entry_or_panic(tree, *idx).size);
+        }
+        children
+    }
+
+    /// Push all directories of the subtree at `idx` that aren't deeper than `options.max_depth` onto `out`
+    /// and return the amount of IO errors in the subtree.
+    fn collect_entries(
+        tree: &Tree,
+        idx: TreeIndex,
+        path: PathBuf,
+        depth: usize,
+        options: &TreeOptions,
+        stats: &mut Statistics,
+        out: &mut Vec<Entry>,
+    ) -> u64 {
+        let entry = get_entry_or_panic(tree, idx);
+        let children = sorted_children(tree, idx, options.sort_by_size_in_bytes);
+        if !entry.entry_type.is_dir() {
+            stats.largest_file_in_bytes = stats.largest_file_in_bytes.max(entry.size);
+            stats.smallest_file_in_bytes = stats.smallest_file_in_bytes.min(entry.size);
+        }
+        let is_shown = depth == 0
+            || (depth <= options.max_depth && (!children.is_empty() || entry.entry_type.is_dir()));
+
+        let entry_position = (is_shown && options.style == TreeStyle::Indented).then(|| {
+            out.push(Entry {
+                path: path.clone(),
+                depth,
+                num_bytes: entry.size,
+                num_errors: 0,
+            });
+            out.len() - 1
+        });
+        let mut num_errors = u64::from(entry.metadata_io_error);
+        for child_idx in children {
+            let child_path = path.join(&get_entry_or_panic(tree, child_idx).name);
+            num_errors +=
+                collect_entries(tree, child_idx, child_path, depth + 1, options, stats, out);
+        }
+        match entry_position {
+            Some(pos) => out[pos].num_errors = num_errors,
+            None if is_shown => out.push(Entry {
+                path,
+                depth,
+                num_bytes: entry.size,
+                num_errors,
+            }),
+            None => {}
+        }
+        num_errors
+    }
+
+    let tree = &traversal.tree;
+    let mut stats = Statistics {
+        entries_traversed: traversal.entries_traversed,
+        smallest_file_in_bytes: u128::MAX,
+        ..Default::default()
+    };
+    let mut roots = sorted_children(tree, traversal.root_index, false);
+    let mut entries_per_root = Vec::new();
+    for path in paths {
+        // Inputs which couldn't be read at all don't make it into the tree.
+        let root = roots
+            .iter()
+            .position(|idx| get_entry_or_panic(tree, *idx).name == path)
+            .map(|pos| roots.remove(pos));
+        let mut entries = Vec::new();
+        match root {
+            Some(idx) => {
+                collect_entries(tree, idx, path, 0, &options, &mut stats, &mut entries);
+            }
+            None => entries.push(Entry {
+                path,
+                depth: 0,
+                num_bytes: 0,
+                num_errors: 1,
+            }),
+        }
+        entries_per_root.push(entries);
+    }
+    if options.sort_by_size_in_bytes {
+        entries_per_root.sort_by_key(|entries| {
+            entries
+                .iter()
+                .find(|e| e.depth == 0)
+                .map_or(0, |e| e.num_bytes)
+        });
+    }
+    if stats.smallest_file_in_bytes == u128::MAX {
+        stats.smallest_file_in_bytes = 0;
+    }
+
+    let (mut total, mut total_errors) = (0, 0);
+    for entry in entries_per_root.into_iter().flatten() {
+        if entry.depth == 0 {
+            total += entry.num_bytes;
+            total_errors += entry.num_errors;
+        }
+        out.add_path(&entry.path, entry.depth, entry.num_bytes, entry.num_errors)?;
+    }
+    if options.compute_total {
+        out.add_total(total, total_errors)?;
+    }
+    out.finish(&stats)?;
+    Ok((
+        WalkResult {
+            num_errors: traversal.io_errors,
+        },
+        stats,
+    ))
+}
+
+/// A destination for the results of an aggregation, which determines their format.
+trait Output {
+    /// Called once for each aggregated `path` at `depth` below the input path it belongs to,
+    /// in the order in which they should be presented. Input paths have a `depth` of 0.
+    fn add_path(
+        &mut self,
+        path: &Path,
+        depth: usize,
+        num_bytes: u128,
+        num_errors: u64,
+    ) -> io::Result<()>;
+    /// Called once after all paths were added, if a total was requested.
+    fn add_total(&mut self, num_bytes: u128, num_errors: u64) -> io::Result<()>;
+    /// Called last to write everything that wasn't written yet.
+    fn finish(&mut self, stats: &Statistics) -> io::Result<()>;
+}
+
+struct HumanOutput<W> {
+    out: W,
+    byte_format: ByteFormat,
+    style: TreeStyle,
+    num_paths: usize,
+}
+
+impl<W: io::Write> HumanOutput<W> {
+    fn new(out: W, byte_format: ByteFormat, style: TreeStyle) -> Self {
+        HumanOutput {
+            out,
+            byte_format,
+            style,
+            num_paths: 0,
+        }
+    }
+}
+
+impl<W: io::Write> Output for HumanOutput<W> {
+    fn add_path(
+        &mut self,
+        path: &Path,
+        depth: usize,
+        num_bytes: u128,
+        num_errors: u64,
+    ) -> io::Result<()> {
+        let color = path_color_of(path);
+        match (self.style, depth, path.file_name()) {
+            (TreeStyle::Indented, 1.., Some(name)) => output_colored_path(
+                &mut self.out,
+                self.byte_format,
+                format_args!(
+                    "{:indent$}{}",
+                    "",
+                    name.to_string_lossy(),
+                    indent = depth * 2
+                ),
                 num_bytes,
                 num_errors,
-                path_color_of(&path),
-            )?;
+                color,
+            ),
+            _ => {
+                if depth == 0 {
+                    self.num_paths += 1;
+                }
+                output_colored_path(
+                    &mut self.out,
+                    self.byte_format,
+                    path.display(),
+                    num_bytes,
+                    num_errors,
+                    color,
+                )
+            }
         }
     }
 
-    if num_roots > 1 && compute_total {
+    fn add_total(&mut self, num_bytes: u128, num_errors: u64) -> io::Result<()> {
+        // A single path already is its own total.
+        if self.num_paths < 2 {
+            return Ok(());
+        }
         output_colored_path(
-            &mut out,
-            &walk_options,
-            Path::new("total"),
-            total,
-            res.num_errors,
+            &mut self.out,
+            self.byte_format,
+            "total",
+            num_bytes,
+            num_errors,
             None,
+        )
+    }
+
+    fn finish(&mut self, _stats: &Statistics) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonPath {
+    path: String,
+    depth: usize,
+    bytes: u128,
+    io_errors: u64,
+}
+
+#[derive(Serialize)]
+struct JsonTotal {
+    bytes: u128,
+    io_errors: u64,
+}
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    paths: &'a [JsonPath],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<&'a JsonTotal>,
+    statistics: &'a Statistics,
+}
+
+struct JsonOutput<W> {
+    out: W,
+    paths: Vec<JsonPath>,
+    total: Option<JsonTotal>,
+}
+
+impl<W: io::Write> JsonOutput<W> {
+    fn new(out: W) -> Self {
+        JsonOutput {
+            out,
+            paths: Vec::new(),
+            total: None,
+        }
+    }
+}
+
+impl<W: io::Write> Output for JsonOutput<W> {
+    fn add_path(
+        &mut self,
+        path: &Path,
+        depth: usize,
+        num_bytes: u128,
+        num_errors: u64,
+    ) -> io::Result<()> {
+        self.paths.push(JsonPath {
+            path: path.to_string_lossy().into_owned(),
+            depth,
+            bytes: num_bytes,
+            io_errors: num_errors,
+        });
+        Ok(())
+    }
+
+    fn add_total(&mut self, num_bytes: u128, num_errors: u64) -> io::Result<()> {
+        self.total = Some(JsonTotal {
+            bytes: num_bytes,
+            io_errors: num_errors,
+        });
+        Ok(())
+    }
+
+    fn finish(&mut self, stats: &Statistics) -> io::Result<()> {
+        serde_json::to_writer_pretty(
+            &mut self.out,
+            &JsonDocument {
+                paths: &self.paths,
+                total: self.total.as_ref(),
+                statistics: stats,
+            },
         )?;
+        writeln!(self.out)
     }
-    Ok((res, stats))
 }
 
 fn path_color_of(path: impl AsRef<Path>) -> Option<Color> {
@@ -137,23 +648,22 @@ fn path_color_of(path: impl AsRef<Path>) -> Option<Color> {
 
 fn output_colored_path(
     out: &mut impl io::Write,
-    options: &WalkOptions,
-    path: impl AsRef<Path>,
+    byte_format: ByteFormat,
+    path: impl fmt::Display,
     num_bytes: u128,
     num_errors: u64,
     path_color: Option<Color>,
 ) -> std::result::Result<(), io::Error> {
-    let size = options.byte_format.display(num_bytes).to_string();
+    let size = byte_format.display(num_bytes).to_string();
     let size = size.green();
-    let size_width = options.byte_format.width();
-    let path = path.as_ref().display();
+    let size_width = byte_format.width();
 
-    let errors = (num_errors != 0)
-        .then(|| {
-            let plural_s = if num_errors > 1 { "s" } else { "" };
-            format!("  <{num_errors} IO Error{plural_s}>")
-        })
-        .unwrap_or_default();
+    let errors = if num_errors != 0 {
+        let plural_s = if num_errors > 1 { "s" } else { "" };
+        format!("  <{num_errors} IO Error{plural_s}>")
+    } else {
+        String::new()
+    };
 
     if let Some(color) = path_color {
         writeln!(out, "{size:>size_width$} {}{errors}", path.color(color))
@@ -163,7 +673,7 @@ fn output_colored_path(
 }
 
 /// Statistics obtained during a filesystem walk
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct Statistics {
     /// The amount of entries we have seen during filesystem traversal
     pub entries_traversed: u64,
@@ -172,3 +682,262 @@ pub struct Statistics {
     /// The size of the largest file encountered in bytes
     pub largest_file_in_bytes: u128,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::TempDir, ExcludeFilter, TraversalSorting};
+
+    fn walk_options() -> WalkOptions {
+        WalkOptions {
+            threads: 1,
+            byte_format: ByteFormat::Metric,
+            apparent_size: true,
+            count_hard_links: false,
+            sorting: TraversalSorting::AlphabeticalByFileName,
+            cross_filesystems: true,
+            ignore_dirs: Vec::new(),
+            exclude: Default::default(),
+            respect_ignore_files: false,
+        }
+    }
+
+    #[test]
+    fn json_output_has_exact_byte_counts_total_and_statistics() -> Result<()> {
+        let mut out = Vec::new();
+        aggregate(
+            &mut out,
+            None::<io::Sink>,
+            walk_options(),
+            OutputFormat::Json,
+            true,
+            true,
+            ["tests/fixtures/sample-02", "tests/fixtures/sample-02/dir"],
+        )?;
+
+        let doc: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(doc["paths"][0]["path"], "tests/fixtures/sample-02/dir");
+        assert_eq!(doc["paths"][0]["bytes"], 1283);
+        assert_eq!(doc["paths"][1]["bytes"], 1540);
+        assert_eq!(doc["paths"][1]["io_errors"], 0);
+        assert_eq!(doc["total"]["bytes"], 1540 + 1283);
+        assert_eq!(doc["statistics"]["largest_file_in_bytes"], 1024);
+        Ok(())
+    }
+
+    #[test]
+    fn tree_output_lists_directories_up_to_max_depth() -> Result<()> {
+        let mut out = Vec::new();
+        aggregate_tree(
+            &mut out,
+            None::<io::Sink>,
+            walk_options(),
+            OutputFormat::Json,
+            TreeOptions {
+                max_depth: 2,
+                style: TreeStyle::Flat,
+                compute_total: true,
+                sort_by_size_in_bytes: true,
+            },
+            vec![
+                "tests/fixtures/sample-02".into(),
+                "tests/fixtures/missing".into(),
+            ],
+        )?;
+
+        let doc: serde_json::Value = serde_json::from_slice(&out)?;
+        let paths: Vec<_> = doc["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                (
+                    p["path"].as_str().unwrap().to_owned(),
+                    p["depth"].as_u64().unwrap(),
+                    p["bytes"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("tests/fixtures/missing".into(), 0, 0),
+                ("tests/fixtures/sample-02/dir/empty-dir".into(), 2, 0),
+                ("tests/fixtures/sample-02/dir/sub".into(), 2, 1024),
+                ("tests/fixtures/sample-02/dir".into(), 1, 1283),
+                ("tests/fixtures/sample-02".into(), 0, 1540),
+            ],
+            "directories come after their children, files are not listed, and missing inputs are errors"
+        );
+        assert_eq!(doc["total"]["bytes"], 1540);
+        assert_eq!(doc["total"]["io_errors"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn tree_statistics_only_consider_files() -> Result<()> {
+        let tmp = TempDir::new("dua-aggregate-tree-statistics")?;
+        let root = tmp.path();
+        std::fs::create_dir(root.join("empty-dir"))?;
+        std::fs::write(root.join("file"), [0; 10])?;
+
+        let mut out = Vec::new();
+        let (_, stats) = aggregate_tree(
+            &mut out,
+            None::<io::Sink>,
+            walk_options(),
+            OutputFormat::Json,
+            TreeOptions {
+                max_depth: 1,
+                style: TreeStyle::Flat,
+                compute_total: false,
+                sort_by_size_in_bytes: false,
+            },
+            vec![root.to_path_buf()],
+        )?;
+        assert_eq!(
+            (stats.smallest_file_in_bytes, stats.largest_file_in_bytes),
+            (10, 10),
+            "empty directories aren't files"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn top_lists_largest_files_ascending_and_counts_hard_links_once() -> Result<()> {
+        let tmp = TempDir::new("dua-aggregate-top")?;
+        let dir = tmp.path();
+        std::fs::write(dir.join("small"), [0; 10])?;
+        std::fs::write(dir.join("large"), [0; 30])?;
+        std::fs::write(dir.join("medium"), [0; 20])?;
+        std::fs::hard_link(dir.join("large"), dir.join("large-link"))?;
+
+        let mut out = Vec::new();
+        top(
+            &mut out,
+            None::<io::Sink>,
+            walk_options(),
+            OutputFormat::Json,
+            2,
+            [dir],
+        )?;
+
+        let doc: serde_json::Value = serde_json::from_slice(&out)?;
+        let sizes: Vec<_> = doc["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["bytes"].as_u64().unwrap())
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![20, 30],
+            "the second link to 'large' isn't counted"
+        );
+        assert_eq!(doc["statistics"]["largest_file_in_bytes"], 30);
+
+        let mut out = Vec::new();
+        top(
+            &mut out,
+            None::<io::Sink>,
+            walk_options(),
+            OutputFormat::Json,
+            10,
+            [dir],
+        )?;
+        let doc: serde_json::Value = serde_json::from_slice(&out)?;
+        let sizes: Vec<_> = doc["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["bytes"].as_u64().unwrap())
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![10, 20, 30],
+            "entries that don't contribute any size aren't listed, even if there is room"
+        );
+        assert_eq!(doc["statistics"]["smallest_file_in_bytes"], 10);
+        Ok(())
+    }
+
+    #[test]
+    fn excluded_directories_do_not_contribute() -> Result<()> {
+        let mut out = Vec::new();
+        aggregate(
+            &mut out,
+            None::<io::Sink>,
+            WalkOptions {
+                exclude: ExcludeFilter::new(["sub", "**/sample-02/a", "sample-02"])?,
+                ..walk_options()
+            },
+            OutputFormat::Json,
+            false,
+            false,
+            ["tests/fixtures/sample-02"],
+        )?;
+
+        let doc: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(
+            doc["paths"][0]["bytes"],
+            1540 - 1024 - 256,
+            "input paths are never excluded"
+        );
+        assert_eq!(
+            doc["statistics"]["entries_traversed"], 7,
+            "neither 'sub' nor its file are seen"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ignored_entries_do_not_contribute_if_ignore_files_are_respected() -> Result<()> {
+        let tmp = TempDir::new("dua-aggregate-ignore-files")?;
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join(".git"))?;
+        std::fs::create_dir_all(root.join("target"))?;
+        std::fs::write(root.join(".gitignore"), "target/\n")?;
+        std::fs::write(root.join("target/a"), [0; 100])?;
+        std::fs::write(root.join("b"), [0; 10])?;
+
+        let bytes = |respect_ignore_files| -> Result<u64> {
+            let mut out = Vec::new();
+            aggregate(
+                &mut out,
+                None::<io::Sink>,
+                WalkOptions {
+                    respect_ignore_files,
+                    ..walk_options()
+                },
+                OutputFormat::Json,
+                false,
+                false,
+                [root.join("target"), root.to_path_buf()],
+            )?;
+            let doc: serde_json::Value = serde_json::from_slice(&out)?;
+            Ok(doc["paths"][1]["bytes"].as_u64().expect("a number"))
+        };
+        assert_eq!(bytes(false)?, 100 + 10 + 8);
+        assert_eq!(bytes(true)?, 10 + 8);
+
+        let mut out = Vec::new();
+        aggregate(
+            &mut out,
+            None::<io::Sink>,
+            WalkOptions {
+                respect_ignore_files: true,
+                ..walk_options()
+            },
+            OutputFormat::Json,
+            false,
+            false,
+            [root.join("target")],
+        )?;
+        let doc: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(
+            doc["paths"][0]["bytes"], 100,
+            "input paths are never ignored"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+//! Writing files and directories into compressed tar archives, and checking that they can be read back.
+use crate::DeletionProgress;
+use std::{
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+/// The kind of compressed archive to write, as determined by its file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// `.tar.gz` or `.tgz`
+    TarGz,
+    /// `.tar.zst` or `.tzst`
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// Return the format matching the extension of `path`, if it's supported.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+/// What was written into an archive, to be compared with what can be read back from it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveSummary {
+    /// The amount of files, directories and symlinks.
+    pub entries: u64,
+    /// The size of all files.
+    pub bytes: u64,
+}
+
+/// A compressed tar archive that files and directories are added to one after another.
+pub struct ArchiveWriter {
+    builder: tar::Builder<Compressor>,
+    summary: ArchiveSummary,
+}
+
+impl ArchiveWriter {
+    /// Create a new archive at `path`, which must not exist yet.
+    pub fn create(path: &Path, format: ArchiveFormat) -> io::Result<Self> {
+        let file = BufWriter::new(
+            fs::File::options()
+                .write(true)
+                .create_new(true)
+                .open(path)?,
+        );
+        let compressor = match format {
+            ArchiveFormat::TarGz => Compressor::Gz(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            )),
+            ArchiveFormat::TarZst => Compressor::Zst(zstd::Encoder::new(file, 0)?),
+        };
+        let mut builder = tar::Builder::new(compressor);
+        builder.follow_symlinks(false);
+        Ok(ArchiveWriter {
+            builder,
+            summary: ArchiveSummary::default(),
+        })
+    }
+
+    /// Add `path` and everything below it, named like `path` but without leading root or parent components,
+    /// and record each file in `progress`.
+    ///
+    /// Symlinks are added as such, without following them.
+    /// Returns the amount of entries that couldn't be added as error.
+    pub fn append(&mut self, path: &Path, progress: &DeletionProgress) -> Result<(), usize> {
+        let name = archive_name(path);
+        let mut num_errors = 0;
+        let walk = jwalk::WalkDir::new(path)
+            .follow_links(false)
+            .skip_hidden(false)
+            .sort(true)
+            .parallelism(jwalk::Parallelism::Serial);
+        // Directories are returned before their contents, as needed when extracting.
+        for entry in walk {
+            if progress.is_cancelled() {
+                break;
+            }
+            let result = entry.map_err(io::Error::from).and_then(|entry| {
+                let src = entry.path();
+                let rel = src
+                    .strip_prefix(path)
+                    .expect("entries are below the walked path");
+                let num_bytes = if entry.file_type.is_file() {
+                    src.symlink_metadata()?.len()
+                } else {
+                    0
+                };
+                self.builder
+                    .append_path_with_name(&src, name.join(rel))
+                    .map(|()| num_bytes)
+            });
+            let num_bytes = *result.as_ref().unwrap_or(&0);
+            if result.is_ok() {
+                self.summary.entries += 1;
+                self.summary.bytes += num_bytes;
+            } else {
+                num_errors += 1;
+            }
+            progress.record(result.map(|_| ()), num_bytes);
+        }
+        match num_errors {
+            0 => Ok(()),
+            n => Err(n),
+        }
+    }
+
+    /// Write the end of the archive and return what was written into it.
+    pub fn finish(self) -> io::Result<ArchiveSummary> {
+        self.builder.into_inner()?.finish()?;
+        Ok(self.summary)
+    }
+}
+
+/// Read the archive at `path` entirely and fail unless it contains what `expected` says.
+pub fn verify_archive(
+    path: &Path,
+    format: ArchiveFormat,
+    expected: ArchiveSummary,
+) -> io::Result<()> {
+    let file = BufReader::new(fs::File::open(path)?);
+    let input: Box<dyn Read> = match format {
+        ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+        ArchiveFormat::TarZst => Box::new(zstd::Decoder::with_buffer(file)?),
+    };
+    let mut archive = tar::Archive::new(input);
+    let mut actual = ArchiveSummary::default();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        actual.entries += 1;
+        actual.bytes += io::copy(&mut entry, &mut io::sink())?;
+    }
+    if actual != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Archive at '{}' contains {} entries with {} bytes, expected {} entries with {} bytes",
+                path.display(),
+                actual.entries,
+                actual.bytes,
+                expected.entries,
+                expected.bytes
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Entries must be relative to be extracted safely.
+fn archive_name(path: &Path) -> PathBuf {
+    let name: PathBuf = path
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect();
+    if name.as_os_str().is_empty() {
+        PathBuf::from("root")
+    } else {
+        name
+    }
+}
+
+enum Compressor {
+    Gz(flate2::write::GzEncoder<BufWriter<fs::File>>),
+    Zst(zstd::Encoder<'static, BufWriter<fs::File>>),
+}
+
+impl Compressor {
+    fn finish(self) -> io::Result<()> {
+        let mut file = match self {
+            Compressor::Gz(encoder) => encoder.finish()?,
+            Compressor::Zst(encoder) => encoder.finish()?,
+        };
+        file.flush()?;
+        file.get_ref().sync_all()
+    }
+}
+
+impl Write for Compressor {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Compressor::Gz(encoder) => encoder.write(buf),
+            Compressor::Zst(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Compressor::Gz(encoder) => encoder.flush(),
+            Compressor::Zst(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn formats_are_determined_by_extension() {
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("a/b.tar.GZ")),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("b.tzst")),
+            Some(ArchiveFormat::TarZst)
+        );
+        assert_eq!(ArchiveFormat::from_path(Path::new("b.zip")), None);
+    }
+
+    #[test]
+    fn archives_contain_all_entries_and_can_be_verified() -> io::Result<()> {
+        let tmp = TempDir::new("dua-archive")?;
+        let root = tmp.path();
+        fs::create_dir_all(root.join("dir/sub"))?;
+        fs::write(root.join("dir/sub/file"), [1; 100])?;
+        fs::write(root.join("file"), [2; 10])?;
+
+        for (name, format) in [
+            ("out.tar.gz", ArchiveFormat::TarGz),
+            ("out.tar.zst", ArchiveFormat::TarZst),
+        ] {
+            let path = root.join(name);
+            let progress = DeletionProgress::default();
+            let mut archive = ArchiveWriter::create(&path, format)?;
+            assert_eq!(archive.append(&root.join("dir"), &progress), Ok(()));
+            assert_eq!(archive.append(&root.join("file"), &progress), Ok(()));
+            let summary = archive.finish()?;
+            assert_eq!(
+                summary,
+                ArchiveSummary {
+                    entries: 4,
+                    bytes: 110
+                }
+            );
+            assert_eq!(progress.files_removed(), 4);
+            verify_archive(&path, format, summary)?;
+            assert!(
+                verify_archive(
+                    &path,
+                    format,
+                    ArchiveSummary {
+                        entries: 5,
+                        ..summary
+                    }
+                )
+                .is_err(),
+                "missing entries are detected"
+            );
+            assert!(
+                ArchiveWriter::create(&path, format).is_err(),
+                "existing files are never overwritten"
+            );
+        }
+        assert_eq!(archive_name(Path::new("/a/../b/./c")), Path::new("a/b/c"));
+        Ok(())
+    }
+}
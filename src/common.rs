@@ -1,6 +1,10 @@
 use crate::crossdev;
+use crate::ignorefilter::IgnoreFilter;
 use crate::traverse::{EntryData, Tree, TreeIndex};
 use byte_unit::{n_gb_bytes, n_gib_bytes, n_mb_bytes, n_mib_bytes, ByteUnit};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::Gitignore;
+use std::ffi::OsStr;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -12,10 +16,6 @@ pub fn get_entry_or_panic(tree: &Tree, node_idx: TreeIndex) -> &EntryData {
         .expect("node should always be retrievable with valid index")
 }
 
-pub(crate) fn get_size_or_panic(tree: &Tree, node_idx: TreeIndex) -> u128 {
-    get_entry_or_panic(tree, node_idx).size
-}
-
 /// Specifies a way to format bytes
 #[derive(Clone, Copy)]
 pub enum ByteFormat {
@@ -158,6 +158,56 @@ impl Throttle {
     }
 }
 
+/// A set of glob patterns to exclude files and directories from a filesystem walk.
+///
+/// Patterns without a path separator, like `*.iso`, are matched against the name of each entry.
+/// All other patterns, like `**/node_modules`, are matched against the entire path of each entry.
+#[derive(Clone)]
+pub struct ExcludeFilter {
+    names: GlobSet,
+    paths: GlobSet,
+}
+
+impl Default for ExcludeFilter {
+    fn default() -> Self {
+        ExcludeFilter {
+            names: GlobSet::empty(),
+            paths: GlobSet::empty(),
+        }
+    }
+}
+
+impl ExcludeFilter {
+    pub fn new(
+        patterns: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, globset::Error> {
+        let (mut names, mut paths) = (GlobSetBuilder::new(), GlobSetBuilder::new());
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let glob: Glob = GlobBuilder::new(pattern).literal_separator(true).build()?;
+            if pattern.contains('/') || pattern.contains(std::path::MAIN_SEPARATOR) {
+                paths.add(glob);
+            } else {
+                names.add(glob);
+            }
+        }
+        Ok(ExcludeFilter {
+            names: names.build()?,
+            paths: paths.build()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.paths.is_empty()
+    }
+
+    /// Return `true` if the entry with `name` at `path` should not be traversed.
+    /// `path` is only computed if there are patterns to match it against.
+    pub fn is_excluded(&self, name: &OsStr, path: impl FnOnce() -> PathBuf) -> bool {
+        self.names.is_match(name) || (!self.paths.is_empty() && self.paths.is_match(path()))
+    }
+}
+
 /// Configures a filesystem walk, including output and formatting options.
 #[derive(Clone)]
 pub struct WalkOptions {
@@ -170,9 +220,19 @@ pub struct WalkOptions {
     pub sorting: TraversalSorting,
     pub cross_filesystems: bool,
     pub ignore_dirs: Vec<PathBuf>,
+    /// Files and directories matching these patterns are skipped, directories won't be entered.
+    pub exclude: ExcludeFilter,
+    /// If set, files and directories ignored by `.gitignore` and `.ignore` files or the global git excludes
+    /// are skipped, directories won't be entered.
+    pub respect_ignore_files: bool,
 }
 
-type WalkDir = jwalk::WalkDirGeneric<((), Option<Result<std::fs::Metadata, jwalk::Error>>)>;
+type WalkState = (
+    IgnoreFilter,
+    Option<Result<std::fs::Metadata, jwalk::Error>>,
+);
+type WalkDir = jwalk::WalkDirGeneric<WalkState>;
+pub(crate) type DirEntry = jwalk::DirEntry<WalkState>;
 
 impl WalkOptions {
     pub(crate) fn iter_from_path(&self, root: &Path, root_device_id: u64) -> WalkDir {
@@ -185,8 +245,35 @@ impl WalkOptions {
             .skip_hidden(false)
             .process_read_dir({
                 let ignore_dirs = self.ignore_dirs.clone();
+                let exclude = self.exclude.clone();
                 let cross_filesystems = self.cross_filesystems;
-                move |_, _, _, dir_entry_results| {
+                let global_ignores = self
+                    .respect_ignore_files
+                    .then(|| Arc::new(Gitignore::global().0));
+                move |depth, parent_path, ignore_filter, dir_entry_results| {
+                    if global_ignores.is_some() {
+                        *ignore_filter = match depth {
+                            None => IgnoreFilter::for_root_parent(parent_path),
+                            Some(_) => ignore_filter.enter(parent_path),
+                        };
+                    }
+                    // Without depth, the only entry is the root, which is never excluded.
+                    if depth.is_some() && (!exclude.is_empty() || global_ignores.is_some()) {
+                        dir_entry_results.retain(|dir_entry_result| match dir_entry_result {
+                            Ok(dir_entry) => {
+                                !exclude.is_excluded(&dir_entry.file_name, || {
+                                    parent_path.join(&dir_entry.file_name)
+                                }) && !global_ignores.as_ref().is_some_and(|global_ignores| {
+                                    ignore_filter.is_ignored(
+                                        &parent_path.join(&dir_entry.file_name),
+                                        dir_entry.file_type.is_dir(),
+                                        global_ignores,
+                                    )
+                                })
+                            }
+                            Err(_) => true,
+                        });
+                    }
                     dir_entry_results.iter_mut().for_each(|dir_entry_result| {
                         if let Ok(dir_entry) = dir_entry_result {
                             let metadata = dir_entry.metadata();
@@ -202,27 +289,37 @@ impl WalkOptions {
                                 if !ok_for_fs || ignore_dirs.contains(&dir_entry.path()) {
                                     dir_entry.read_children_path = None;
                                 }
+                                // Directories only need their metadata for the modification time,
+                                // so failing to obtain it is no error.
+                                if metadata.is_ok() {
+                                    dir_entry.client_state = Some(metadata);
+                                }
                             }
                         }
                     })
                 }
             })
-            .parallelism(match self.threads {
-                0 => jwalk::Parallelism::RayonDefaultPool {
-                    busy_timeout: std::time::Duration::from_secs(1),
-                },
-                1 => jwalk::Parallelism::Serial,
-                _ => jwalk::Parallelism::RayonExistingPool {
-                    pool: jwalk::rayon::ThreadPoolBuilder::new()
-                        .stack_size(128 * 1024)
-                        .num_threads(self.threads)
-                        .thread_name(|idx| format!("dua-fs-walk-{idx}"))
-                        .build()
-                        .expect("fields we set cannot fail")
-                        .into(),
-                    busy_timeout: None,
-                },
-            })
+            .parallelism(parallelism(self.threads, "dua-fs-walk"))
+    }
+}
+
+/// Return the parallelism for a `jwalk` walk with `threads`, whose threads are named after `thread_name`.
+pub(crate) fn parallelism(threads: usize, thread_name: &'static str) -> jwalk::Parallelism {
+    match threads {
+        0 => jwalk::Parallelism::RayonDefaultPool {
+            busy_timeout: std::time::Duration::from_secs(1),
+        },
+        1 => jwalk::Parallelism::Serial,
+        _ => jwalk::Parallelism::RayonExistingPool {
+            pool: jwalk::rayon::ThreadPoolBuilder::new()
+                .stack_size(128 * 1024)
+                .num_threads(threads)
+                .thread_name(move |idx| format!("{thread_name}-{idx}"))
+                .build()
+                .expect("fields we set cannot fail")
+                .into(),
+            busy_timeout: None,
+        },
     }
 }
 
@@ -238,3 +335,30 @@ impl WalkResult {
         i32::from(self.num_errors > 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclude_filter_matches_names_without_separator_and_paths_otherwise() {
+        let filter = ExcludeFilter::new(["*.iso", "**/node_modules", "/srv/*/cache"]).unwrap();
+        let excluded = |path: &str| {
+            let path = Path::new(path);
+            filter.is_excluded(path.file_name().unwrap(), || path.to_owned())
+        };
+
+        assert!(excluded("image.iso"));
+        assert!(excluded("./a/b/image.iso"), "names match at any depth");
+        assert!(excluded("./a/node_modules"));
+        assert!(excluded("node_modules"), "'**/' also matches no directory");
+        assert!(!excluded("./a/node_modules_backup"));
+        assert!(excluded("/srv/www/cache"));
+        assert!(
+            !excluded("/srv/www/deep/cache"),
+            "'*' doesn't match path separators"
+        );
+        assert!(ExcludeFilter::default().is_empty());
+        assert!(ExcludeFilter::new(["["]).is_err());
+    }
+}
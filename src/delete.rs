@@ -0,0 +1,190 @@
+//! Removing files and directories from disk, using the same thread pool setup as the filesystem walk.
+use crate::common::parallelism;
+use std::{
+    fs, io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// The amount of files and bytes removed so far by [`delete_recursively()`], which can be observed from other
+/// threads while the deletion is ongoing, and used to cancel it.
+#[derive(Debug, Default)]
+pub struct DeletionProgress {
+    files_removed: AtomicU64,
+    bytes_removed: AtomicU64,
+    errors: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl DeletionProgress {
+    /// Stop removing entries as soon as possible, leaving everything not yet removed in place.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The amount of files, symlinks and directories removed.
+    pub fn files_removed(&self) -> u64 {
+        self.files_removed.load(Ordering::Relaxed)
+    }
+
+    /// The apparent size of all files that were removed.
+    pub fn bytes_removed(&self) -> u64 {
+        self.bytes_removed.load(Ordering::Relaxed)
+    }
+
+    /// The amount of entries that couldn't be read or removed.
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record(&self, result: io::Result<()>, num_bytes: u64) {
+        match result {
+            Ok(()) => {
+                self.files_removed.fetch_add(1, Ordering::Relaxed);
+                self.bytes_removed.fetch_add(num_bytes, Ordering::Relaxed);
+            }
+            Err(err) => self.record_error(err.kind()),
+        }
+    }
+
+    pub(crate) fn record_error(&self, kind: io::ErrorKind) {
+        // Entries that are already gone don't need to be removed anymore.
+        if kind != io::ErrorKind::NotFound {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Remove `path` and everything below it, deleting the files of each directory on one of `threads`, and record
+/// what was removed in `progress`.
+///
+/// Symlinks are never followed, but removed themselves.
+/// If `progress` is cancelled, nothing else is removed and `Ok(())` is returned, even though `path` may still exist.
+/// Returns the amount of entries that couldn't be removed as error.
+pub fn delete_recursively(
+    path: &Path,
+    threads: usize,
+    progress: &Arc<DeletionProgress>,
+) -> Result<(), usize> {
+    let errors_before = progress.errors();
+    match path.symlink_metadata() {
+        Ok(m) if m.is_dir() => delete_directory(path, threads, progress),
+        // Try to remove what we can't look at as if it was a file.
+        meta => progress.record(fs::remove_file(path), meta.map_or(0, |m| m.len())),
+    }
+    match progress.errors() - errors_before {
+        0 => Ok(()),
+        num_errors => Err(num_errors as usize),
+    }
+}
+
+fn delete_directory(path: &Path, threads: usize, progress: &Arc<DeletionProgress>) {
+    let mut dirs = Vec::new();
+    let walk = jwalk::WalkDir::new(path)
+        .follow_links(false)
+        .skip_hidden(false)
+        .sort(false)
+        .process_read_dir({
+            let progress = Arc::clone(progress);
+            move |_depth, parent_path, _state, dir_entry_results| {
+                if progress.is_cancelled() {
+                    dir_entry_results.clear();
+                    return;
+                }
+                // Remove everything but directories while they are read, leaving only directories to be walked.
+                dir_entry_results.retain(|dir_entry_result| match dir_entry_result {
+                    Ok(dir_entry) if !dir_entry.file_type.is_dir() => {
+                        let path = parent_path.join(&dir_entry.file_name);
+                        let num_bytes = path.symlink_metadata().map_or(0, |m| m.len());
+                        progress.record(fs::remove_file(path), num_bytes);
+                        false
+                    }
+                    _ => true,
+                })
+            }
+        })
+        .parallelism(parallelism(threads, "dua-fs-delete"));
+    for entry in walk {
+        if progress.is_cancelled() {
+            return;
+        }
+        match entry {
+            Ok(entry) => dirs.push(entry.path()),
+            Err(err) => {
+                progress.record_error(err.io_error().map_or(io::ErrorKind::Other, |e| e.kind()))
+            }
+        }
+    }
+
+    // Directories are walked before their contents, and are removed after them.
+    for dir in dirs.into_iter().rev() {
+        if progress.is_cancelled() {
+            return;
+        }
+        progress.record(fs::remove_dir(&dir).or_else(|_| fs::remove_file(dir)), 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn directories_are_removed_with_all_contents_without_following_symlinks() -> io::Result<()> {
+        let tmp = TempDir::new("dua-delete")?;
+        let root = tmp.path();
+        let target = root.join("target");
+        let to_delete = root.join("to-delete");
+        fs::create_dir_all(&target)?;
+        fs::write(target.join("keep"), [0; 10])?;
+
+        for threads in [1, 4] {
+            for dir in ["a/b", "a/c", "d"] {
+                fs::create_dir_all(to_delete.join(dir))?;
+                fs::write(to_delete.join(dir).join("file"), [0; 100])?;
+            }
+            #[cfg(unix)]
+            std::os::unix::fs::symlink("../../target", to_delete.join("a/b/link"))?;
+
+            let progress = Arc::new(DeletionProgress::default());
+            assert_eq!(delete_recursively(&to_delete, threads, &progress), Ok(()));
+            assert!(!to_delete.exists());
+            assert!(target.join("keep").is_file(), "symlinks aren't followed");
+            #[cfg(unix)]
+            assert_eq!(
+                (progress.files_removed(), progress.bytes_removed()),
+                (3 + 1 + 5, 300 + "../../target".len() as u64)
+            );
+            assert_eq!(progress.errors(), 0);
+        }
+
+        fs::create_dir_all(to_delete.join("a"))?;
+        let progress = Arc::new(DeletionProgress::default());
+        progress.cancel();
+        assert_eq!(delete_recursively(&to_delete, 2, &progress), Ok(()));
+        assert!(
+            to_delete.join("a").is_dir(),
+            "nothing is removed once cancelled"
+        );
+
+        let file = to_delete.join("file");
+        fs::write(&file, [0; 100])?;
+        let progress = Arc::new(DeletionProgress::default());
+        assert_eq!(delete_recursively(&file, 2, &progress), Ok(()));
+        assert_eq!(progress.files_removed(), 1);
+        assert_eq!(
+            delete_recursively(&file, 2, &progress),
+            Ok(()),
+            "removing what doesn't exist is no error"
+        );
+        Ok(())
+    }
+}
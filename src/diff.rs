@@ -0,0 +1,327 @@
+use crate::traverse::{Traversal, Tree, TreeIndex};
+use crate::{get_entry_or_panic, ByteFormat, OutputFormat};
+use owo_colors::{AnsiColors as Color, OwoColorize};
+use petgraph::Direction;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// A path that is present in at least one of two trees, along with its node in each of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedEntry {
+    /// The path of the entry, starting with the name of the input path it was found in.
+    pub path: PathBuf,
+    pub old: Option<TreeIndex>,
+    pub new: Option<TreeIndex>,
+}
+
+/// Match all entries of the `old` and `new` tree by their path, and return them in pre-order,
+/// without the root nodes which have no name.
+pub fn match_trees(
+    old: &Tree,
+    old_root: TreeIndex,
+    new: &Tree,
+    new_root: TreeIndex,
+) -> Vec<MatchedEntry> {
+    let mut out = Vec::new();
+    let mut stack = vec![(PathBuf::new(), Some(old_root), Some(new_root))];
+    while let Some((path, old_idx, new_idx)) = stack.pop() {
+        let mut old_children: HashMap<&Path, TreeIndex> = old_idx
+            .map(|idx| {
+                old.neighbors_directed(idx, Direction::Outgoing)
+                    .map(|child_idx| (get_entry_or_panic(old, child_idx).name.as_path(), child_idx))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut children: Vec<_> = new_idx
+            .map(|idx| {
+                new.neighbors_directed(idx, Direction::Outgoing)
+                    .map(|child_idx| {
+                        let name = get_entry_or_panic(new, child_idx).name.as_path();
+                        (name, old_children.remove(name), Some(child_idx))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        children.extend(
+            old_children
+                .into_iter()
+                .map(|(name, idx)| (name, Some(idx), None)),
+        );
+        children.sort_by(|l, r| r.0.cmp(l.0));
+
+        stack.extend(
+            children
+                .into_iter()
+                .map(|(name, old_idx, new_idx)| (path.join(name), old_idx, new_idx)),
+        );
+        if !path.as_os_str().is_empty() {
+            out.push(MatchedEntry {
+                path,
+                old: old_idx,
+                new: new_idx,
+            });
+        }
+    }
+    out
+}
+
+/// Return the node in `other` with the same path as `node_idx` in `tree`, if there is one.
+pub fn matching_node(
+    tree: &Tree,
+    node_idx: TreeIndex,
+    other: &Tree,
+    other_root: TreeIndex,
+) -> Option<TreeIndex> {
+    let mut names = Vec::new();
+    let mut idx = node_idx;
+    while let Some(parent_idx) = tree.neighbors_directed(idx, Direction::Incoming).next() {
+        names.push(get_entry_or_panic(tree, idx).name.as_path());
+        idx = parent_idx;
+    }
+    names
+        .into_iter()
+        .rev()
+        .try_fold(other_root, |parent_idx, name| {
+            other
+                .neighbors_directed(parent_idx, Direction::Outgoing)
+                .find(|&child_idx| get_entry_or_panic(other, child_idx).name == name)
+        })
+}
+
+/// The difference in size of a directory between two traversals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeChange {
+    pub path: PathBuf,
+    /// The size in the old traversal, or `None` if the directory didn't exist back then.
+    pub old_bytes: Option<u128>,
+    /// The size in the new traversal, or `None` if the directory doesn't exist anymore.
+    pub new_bytes: Option<u128>,
+}
+
+impl SizeChange {
+    /// The amount of bytes the directory grew by, or shrunk by if negative.
+    pub fn delta(&self) -> i128 {
+        self.new_bytes.unwrap_or(0) as i128 - self.old_bytes.unwrap_or(0) as i128
+    }
+}
+
+/// Return all directories that changed in size between the `old` and the `new` traversal, sorted by the absolute
+/// amount of change, descending.
+///
+/// Directories are matched by their path, and top-level entries by the input path they were traversed with.
+pub fn directory_changes(old: &Traversal, new: &Traversal) -> Vec<SizeChange> {
+    let is_dir = |tree: &Tree, idx: Option<TreeIndex>| {
+        idx.is_some_and(|idx| {
+            tree.neighbors_directed(idx, Direction::Outgoing)
+                .next()
+                .is_some()
+        })
+    };
+    let size =
+        |tree: &Tree, idx: Option<TreeIndex>| idx.map(|idx| get_entry_or_panic(tree, idx).size);
+
+    let mut changes: Vec<_> = match_trees(&old.tree, old.root_index, &new.tree, new.root_index)
+        .into_iter()
+        .filter(|m| is_dir(&old.tree, m.old) || is_dir(&new.tree, m.new))
+        .map(|m| SizeChange {
+            old_bytes: size(&old.tree, m.old),
+            new_bytes: size(&new.tree, m.new),
+            path: m.path,
+        })
+        .filter(|change| change.delta() != 0)
+        .collect();
+    changes.sort_by(|l, r| {
+        r.delta()
+            .unsigned_abs()
+            .cmp(&l.delta().unsigned_abs())
+            .then_with(|| l.path.cmp(&r.path))
+    });
+    changes
+}
+
+/// Write all directories that changed in size between the `old` and the `new` traversal to `out`,
+/// as obtained by [`directory_changes()`].
+pub fn diff(
+    mut out: impl io::Write,
+    output_format: OutputFormat,
+    byte_format: ByteFormat,
+    old: &Traversal,
+    new: &Traversal,
+) -> io::Result<()> {
+    let changes = directory_changes(old, new);
+    match output_format {
+        OutputFormat::Human => {
+            for change in &changes {
+                output_colored_change(&mut out, byte_format, change)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            #[derive(Serialize)]
+            struct JsonChange<'a> {
+                path: std::borrow::Cow<'a, str>,
+                old_bytes: Option<u128>,
+                new_bytes: Option<u128>,
+                delta_bytes: i128,
+            }
+            #[derive(Serialize)]
+            struct JsonDocument<'a> {
+                directories: Vec<JsonChange<'a>>,
+            }
+            let document = JsonDocument {
+                directories: changes
+                    .iter()
+                    .map(|change| JsonChange {
+                        path: change.path.to_string_lossy(),
+                        old_bytes: change.old_bytes,
+                        new_bytes: change.new_bytes,
+                        delta_bytes: change.delta(),
+                    })
+                    .collect(),
+            };
+            serde_json::to_writer_pretty(&mut out, &document)?;
+            writeln!(out)
+        }
+    }
+}
+
+/// Return the signed, formatted `delta`, which is as wide as the column used for byte counts plus one.
+pub fn format_delta(byte_format: ByteFormat, delta: i128) -> String {
+    format!(
+        "{sign}{:>width$}",
+        byte_format.display(delta.unsigned_abs()).to_string(),
+        sign = if delta < 0 { '-' } else { '+' },
+        width = byte_format.width()
+    )
+}
+
+fn output_colored_change(
+    out: &mut impl io::Write,
+    byte_format: ByteFormat,
+    change: &SizeChange,
+) -> io::Result<()> {
+    let delta = format_delta(byte_format, change.delta());
+    let delta = delta.color(if change.delta() > 0 {
+        Color::Red
+    } else {
+        Color::Green
+    });
+    let note = match (change.old_bytes, change.new_bytes) {
+        (None, _) => "  <new>",
+        (_, None) => "  <removed>",
+        _ => "",
+    };
+    writeln!(out, "{delta} {}{note}", change.path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traverse::EntryData;
+
+    fn traversal(entries: &[(&str, u128)]) -> Traversal {
+        let mut tree = Tree::new();
+        let root_index = tree.add_node(EntryData::default());
+        let mut indices = HashMap::<PathBuf, TreeIndex>::new();
+        for (path, size) in entries {
+            let path = Path::new(path);
+            let parent_idx = path
+                .parent()
+                .and_then(|p| indices.get(p).copied())
+                .unwrap_or(root_index);
+            let idx = tree.add_node(EntryData {
+                name: if parent_idx == root_index {
+                    path.into()
+                } else {
+                    path.file_name().expect("a name").into()
+                },
+                size: *size,
+                ..Default::default()
+            });
+            tree.add_edge(parent_idx, idx, ());
+            indices.insert(path.into(), idx);
+        }
+        Traversal {
+            tree,
+            root_index,
+            entries_traversed: entries.len() as u64,
+            start: std::time::Instant::now(),
+            elapsed: None,
+            io_errors: 0,
+            total_bytes: None,
+        }
+    }
+
+    #[test]
+    fn directory_changes_are_sorted_by_absolute_delta() {
+        let old = traversal(&[
+            ("r", 300),
+            ("r/grows", 100),
+            ("r/grows/f", 100),
+            ("r/shrinks", 200),
+            ("r/shrinks/f", 200),
+            ("r/removed", 1),
+            ("r/removed/f", 1),
+        ]);
+        let new = traversal(&[
+            ("r", 145),
+            ("r/grows", 110),
+            ("r/grows/f", 110),
+            ("r/shrinks", 20),
+            ("r/shrinks/f", 20),
+            ("r/added", 15),
+            ("r/added/f", 15),
+        ]);
+
+        let change = |path: &str, old_bytes, new_bytes| SizeChange {
+            path: path.into(),
+            old_bytes,
+            new_bytes,
+        };
+        assert_eq!(
+            directory_changes(&old, &new),
+            vec![
+                change("r/shrinks", Some(200), Some(20)),
+                change("r", Some(300), Some(145)),
+                change("r/added", None, Some(15)),
+                change("r/grows", Some(100), Some(110)),
+                change("r/removed", Some(1), None),
+            ],
+            "files aren't listed, and neither are directories that didn't change"
+        );
+        assert!(directory_changes(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn matching_node_finds_nodes_by_path() {
+        let old = traversal(&[("r", 0), ("r/a", 0), ("r/a/b", 0)]);
+        let new = traversal(&[("r", 0), ("r/c", 0), ("r/a", 0), ("r/a/b", 0)]);
+        let entries = match_trees(&old.tree, old.root_index, &new.tree, new.root_index);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|m| m.path.to_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["r", "r/a", "r/a/b", "r/c"],
+            "pre-order, sorted by name"
+        );
+        for m in &entries {
+            if let Some(new_idx) = m.new {
+                assert_eq!(
+                    matching_node(&new.tree, new_idx, &old.tree, old.root_index),
+                    m.old
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn deltas_are_formatted_with_sign() {
+        assert_eq!(format_delta(ByteFormat::Bytes, 5), "+         5 b");
+        assert_eq!(format_delta(ByteFormat::Bytes, -5), "-         5 b");
+    }
+}
@@ -0,0 +1,120 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The names of files whose patterns apply to the directory they are in, and all directories below it.
+/// Patterns in later files take precedence over the ones in earlier files.
+const IGNORE_FILE_NAMES: &[&str] = &[".gitignore", ".ignore"];
+
+/// The ignore files of a directory and all of its parent directories, to be inherited by
+/// sub-directories during a filesystem walk.
+#[derive(Debug, Default, Clone)]
+pub struct IgnoreFilter {
+    innermost: Option<Arc<Level>>,
+}
+
+#[derive(Debug)]
+struct Level {
+    matcher: Gitignore,
+    parent: IgnoreFilter,
+}
+
+impl IgnoreFilter {
+    /// Return a filter for the directory containing the root of a walk, which also respects the ignore files
+    /// of its parent directories up to the root of the git repository it is in, if there is any.
+    pub fn for_root_parent(dir: &Path) -> Self {
+        let mut dirs = Vec::new();
+        for dir in dir.ancestors() {
+            dirs.push(dir);
+            if dir.join(".git").exists() {
+                return dirs
+                    .into_iter()
+                    .rev()
+                    .fold(IgnoreFilter::default(), |filter, dir| filter.enter(dir));
+            }
+        }
+        IgnoreFilter::default()
+    }
+
+    /// Return a filter for `dir`, which is the current filter along with the ignore files in `dir`, if there are any.
+    pub fn enter(&self, dir: &Path) -> Self {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut has_ignore_file = false;
+        for name in IGNORE_FILE_NAMES {
+            let path = dir.join(name);
+            if path.is_file() {
+                // Invalid lines are skipped, like git does.
+                builder.add(path);
+                has_ignore_file = true;
+            }
+        }
+        match builder.build() {
+            Ok(matcher) if has_ignore_file && !matcher.is_empty() => IgnoreFilter {
+                innermost: Some(Arc::new(Level {
+                    matcher,
+                    parent: self.clone(),
+                })),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Return `true` if `path` is ignored by the innermost ignore file that matches it, or by `global` if there is none.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool, global: &Gitignore) -> bool {
+        let mut level = self.innermost.as_deref();
+        while let Some(Level { matcher, parent }) = level {
+            match matcher.matched(path, is_dir) {
+                Match::None => level = parent.innermost.as_deref(),
+                m => return m.is_ignore(),
+            }
+        }
+        global.matched(path, is_dir).is_ignore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::fs;
+
+    #[test]
+    fn inner_ignore_files_take_precedence_and_global_excludes_apply_last() -> std::io::Result<()> {
+        let tmp = TempDir::new("dua-ignorefilter")?;
+        let root = tmp.path();
+        fs::create_dir_all(root.join("repo/.git"))?;
+        fs::create_dir_all(root.join("repo/sub"))?;
+        fs::write(root.join(".gitignore"), "*")?;
+        fs::write(root.join("repo/.gitignore"), "*.log\ntarget/\n")?;
+        fs::write(root.join("repo/sub/.gitignore"), "!keep.log\n")?;
+        fs::write(root.join("repo/sub/.ignore"), "secret.txt\n")?;
+        let global = {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add_line(None, "*.swp").unwrap();
+            builder.build().unwrap()
+        };
+
+        let repo = IgnoreFilter::for_root_parent(&root.join("repo"));
+        let sub = repo.enter(&root.join("repo/sub"));
+        let ignored = |filter: &IgnoreFilter, path: &str, is_dir| {
+            filter.is_ignored(&root.join(path), is_dir, &global)
+        };
+
+        assert!(
+            !ignored(&repo, "repo/a.txt", false),
+            "ignore files outside the repository don't apply"
+        );
+        assert!(ignored(&repo, "repo/a.log", false));
+        assert!(ignored(&repo, "repo/target", true));
+        assert!(!ignored(&repo, "repo/target", false), "only directories");
+        assert!(ignored(&repo, "repo/a.swp", false));
+        assert!(ignored(&sub, "repo/sub/a.log", false));
+        assert!(!ignored(&sub, "repo/sub/keep.log", false));
+        assert!(ignored(&sub, "repo/sub/secret.txt", false));
+        assert!(!ignored(&repo, "repo/secret.txt", false));
+        assert!(!ignored(&IgnoreFilter::default(), "repo/a.log", false));
+
+        Ok(())
+    }
+}
@@ -100,6 +100,10 @@ impl DisplayByteVisualization {
 pub struct DisplayOptions {
     pub byte_format: ByteFormat,
     pub byte_vis: ByteVisualization,
+    /// If true, show how long ago each entry was last modified.
+    pub show_mtime: bool,
+    /// If true, show how many entries each directory contains, recursively.
+    pub show_entry_count: bool,
 }
 
 impl From<WalkOptions> for DisplayOptions {
@@ -107,6 +111,8 @@ impl From<WalkOptions> for DisplayOptions {
         DisplayOptions {
             byte_format,
             byte_vis: ByteVisualization::default(),
+            show_mtime: false,
+            show_entry_count: false,
         }
     }
 }
@@ -1,7 +1,8 @@
-use crate::interactive::path_of;
 use dua::traverse::{EntryData, Tree, TreeIndex};
+use globset::{GlobBuilder, GlobMatcher};
 use itertools::Itertools;
 use petgraph::Direction;
+use std::{collections::HashSet, path::Path, time::SystemTime};
 use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Default, Debug, Copy, Clone, PartialOrd, PartialEq, Eq)]
@@ -9,14 +10,96 @@ pub enum SortMode {
     #[default]
     SizeDescending,
     SizeAscending,
+    NameAscending,
+    NameDescending,
+    CountDescending,
+    CountAscending,
+    MTimeDescending,
+    MTimeAscending,
 }
 
 impl SortMode {
     pub fn toggle_size(&mut self) {
         use SortMode::*;
         *self = match self {
-            SizeAscending => SizeDescending,
             SizeDescending => SizeAscending,
+            _ => SizeDescending,
+        }
+    }
+
+    pub fn toggle_name(&mut self) {
+        use SortMode::*;
+        *self = match self {
+            NameAscending => NameDescending,
+            _ => NameAscending,
+        }
+    }
+
+    pub fn toggle_count(&mut self) {
+        use SortMode::*;
+        *self = match self {
+            CountDescending => CountAscending,
+            _ => CountDescending,
+        }
+    }
+
+    pub fn toggle_mtime(&mut self) {
+        use SortMode::*;
+        *self = match self {
+            MTimeDescending => MTimeAscending,
+            _ => MTimeDescending,
+        }
+    }
+
+    /// A short description of what the entries are sorted by, and in which direction.
+    pub fn label(self) -> &'static str {
+        use SortMode::*;
+        match self {
+            SizeDescending => "by size ↓",
+            SizeAscending => "by size ↑",
+            NameAscending => "by name ↑",
+            NameDescending => "by name ↓",
+            CountDescending => "by entries ↓",
+            CountAscending => "by entries ↑",
+            MTimeDescending => "by mtime ↓",
+            MTimeAscending => "by mtime ↑",
+        }
+    }
+}
+
+/// Narrows the entries of a directory to the ones whose name matches a pattern.
+///
+/// Patterns with glob characters like `*.log` must match the entire name, all others may match any part of it.
+/// Matching ignores case.
+#[derive(Debug, Clone)]
+pub struct EntriesFilter {
+    pub pattern: String,
+    lowercase_pattern: String,
+    glob: Option<GlobMatcher>,
+}
+
+impl EntriesFilter {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        let glob = pattern
+            .contains(['*', '?', '['])
+            .then(|| GlobBuilder::new(&pattern).case_insensitive(true).build())
+            .and_then(Result::ok)
+            .map(|glob| glob.compile_matcher());
+        EntriesFilter {
+            lowercase_pattern: pattern.to_lowercase(),
+            pattern,
+            glob,
+        }
+    }
+
+    pub fn matches(&self, name: &Path) -> bool {
+        match &self.glob {
+            Some(glob) => glob.is_match(name),
+            None => name
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&self.lowercase_pattern),
         }
     }
 }
@@ -28,28 +111,147 @@ pub struct EntryDataBundle {
     pub exists: bool,
 }
 
+/// Return the entries of `node_idx` like [`sorted_entries()`], but only the ones matching `filter` if it is set.
+pub fn sorted_and_filtered_entries(
+    tree: &Tree,
+    node_idx: TreeIndex,
+    sorting: SortMode,
+    filter: Option<&EntriesFilter>,
+) -> Vec<EntryDataBundle> {
+    let mut entries = sorted_entries(tree, node_idx, sorting);
+    if let Some(filter) = filter {
+        entries.retain(|e| filter.matches(&e.data.name));
+    }
+    entries
+}
+
+/// Return `entries` with the sorted entries of each directory in `expanded` right after it, recursively.
+pub fn with_expanded_entries(
+    tree: &Tree,
+    entries: Vec<EntryDataBundle>,
+    sorting: SortMode,
+    expanded: &HashSet<TreeIndex>,
+) -> Vec<EntryDataBundle> {
+    let mut stack: Vec<_> = entries.into_iter().rev().collect();
+    let mut entries = Vec::with_capacity(stack.len());
+    while let Some(entry) = stack.pop() {
+        if expanded.contains(&entry.index) {
+            stack.extend(sorted_entries(tree, entry.index, sorting).into_iter().rev());
+        }
+        entries.push(entry);
+    }
+    entries
+}
+
 pub fn sorted_entries(tree: &Tree, node_idx: TreeIndex, sorting: SortMode) -> Vec<EntryDataBundle> {
     use SortMode::*;
     tree.neighbors_directed(node_idx, Direction::Outgoing)
         .filter_map(|idx| {
-            tree.node_weight(idx).map(|w| {
-                let p = path_of(tree, idx);
-                let pm = p.symlink_metadata();
-                EntryDataBundle {
-                    index: idx,
-                    data: w.clone(),
-                    exists: pm.is_ok(),
-                    is_dir: pm.ok().map_or(false, |m| m.is_dir()),
-                }
+            tree.node_weight(idx).map(|w| EntryDataBundle {
+                index: idx,
+                data: w.clone(),
+                exists: w.entry_type.exists(),
+                is_dir: w.entry_type.is_dir(),
             })
         })
         .sorted_by(|l, r| match sorting {
             SizeDescending => r.data.size.cmp(&l.data.size),
             SizeAscending => l.data.size.cmp(&r.data.size),
+            NameAscending => l.data.name.cmp(&r.data.name),
+            NameDescending => r.data.name.cmp(&l.data.name),
+            CountDescending => r.data.entry_count.cmp(&l.data.entry_count),
+            CountAscending => l.data.entry_count.cmp(&r.data.entry_count),
+            MTimeDescending => r.data.mtime.cmp(&l.data.mtime),
+            MTimeAscending => l.data.mtime.cmp(&r.data.mtime),
         })
         .collect()
 }
 
+/// The width of the column produced by [`format_age()`].
+pub const AGE_COLUMN_WIDTH: usize = 14;
+
+/// Return how long ago `then` was from `now`, like "3 years ago", in the largest unit that fits.
+pub fn format_age(then: SystemTime, now: SystemTime) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const UNITS: &[(u64, &str)] = &[
+        (365 * DAY, "year"),
+        (30 * DAY, "month"),
+        (DAY, "day"),
+        (HOUR, "hour"),
+        (MINUTE, "minute"),
+        (1, "second"),
+    ];
+    let secs = match now.duration_since(then) {
+        Ok(age) => age.as_secs(),
+        Err(_) => return "in the future".into(),
+    };
+    UNITS
+        .iter()
+        .find(|(unit_secs, _)| secs >= *unit_secs)
+        .map(|(unit_secs, name)| {
+            let amount = secs / unit_secs;
+            format!("{amount} {name}{} ago", if amount == 1 { "" } else { "s" })
+        })
+        .unwrap_or_else(|| "just now".into())
+}
+
+/// Return `time` like "2023-06-01 12:34:56 UTC", or relative to the epoch if it's before it.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since_epoch) => since_epoch.as_secs(),
+        Err(err) => return format!("{}s before 1970", err.duration().as_secs()),
+    };
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+    // Turn days since the epoch into a date of the proleptic Gregorian calendar, in eras of 400 years
+    // starting on March 1st so leap days come last.
+    let days = days + 719_468;
+    let (era, day_of_era) = (days / 146_097, days % 146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+/// The width of the column produced by [`format_count()`].
+pub const COUNT_COLUMN_WIDTH: usize = 5;
+
+/// Return `count` with at most 3 significant digits and a metric suffix, like "120k".
+pub fn format_count(count: u64) -> String {
+    const SUFFIXES: &[char] = &['k', 'M', 'G', 'T', 'P', 'E'];
+    if count < 1000 {
+        return count.to_string();
+    }
+    let mut value = count as f64;
+    let mut suffixes = SUFFIXES.iter();
+    let mut suffix = 'k';
+    while value >= 1000.0 {
+        value /= 1000.0;
+        suffix = *suffixes
+            .next()
+            .expect("u64 has no more than 6 groups of 3 digits");
+    }
+    if value < 10.0 {
+        format!("{value:.1}{suffix}")
+    } else {
+        format!("{value:.0}{suffix}")
+    }
+}
+
 pub fn fit_string_graphemes_with_ellipsis(
     s: impl Into<String>,
     path_graphemes_count: usize,
@@ -86,6 +288,101 @@ pub fn fit_string_graphemes_with_ellipsis(
 mod tests {
     use super::*;
 
+    #[test]
+    fn sort_mode_keys_toggle_direction_or_start_with_the_natural_one() {
+        use SortMode::*;
+        let toggled = |mut mode: SortMode, toggle: fn(&mut SortMode)| {
+            toggle(&mut mode);
+            mode
+        };
+        assert_eq!(
+            toggled(SizeDescending, SortMode::toggle_size),
+            SizeAscending
+        );
+        assert_eq!(
+            toggled(SizeAscending, SortMode::toggle_size),
+            SizeDescending
+        );
+        assert_eq!(
+            toggled(NameAscending, SortMode::toggle_size),
+            SizeDescending
+        );
+        assert_eq!(toggled(SizeAscending, SortMode::toggle_name), NameAscending);
+        assert_eq!(
+            toggled(NameAscending, SortMode::toggle_name),
+            NameDescending
+        );
+        assert_eq!(
+            toggled(SizeDescending, SortMode::toggle_count),
+            CountDescending
+        );
+        assert_eq!(
+            toggled(CountDescending, SortMode::toggle_count),
+            CountAscending
+        );
+        assert_eq!(
+            toggled(CountAscending, SortMode::toggle_mtime),
+            MTimeDescending
+        );
+        assert_eq!(
+            toggled(MTimeDescending, SortMode::toggle_mtime),
+            MTimeAscending
+        );
+    }
+
+    #[test]
+    fn entries_filter_matches_substrings_or_globs_ignoring_case() {
+        let matches =
+            |pattern: &str, name: &str| EntriesFilter::new(pattern).matches(Path::new(name));
+        assert!(matches("log", "Build.LOG"));
+        assert!(matches("", "anything"));
+        assert!(!matches("log", "lo.g"));
+        assert!(matches("*.log", "a.LOG"));
+        assert!(!matches("*.log", "a.log.gz"), "globs match the entire name");
+        assert!(matches("[", "a[b"), "invalid globs are substrings");
+    }
+
+    #[test]
+    fn ages_are_formatted_in_the_largest_fitting_unit() {
+        use std::time::Duration;
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 365 * 24 * 3600);
+        let age = |secs| format_age(now - Duration::from_secs(secs), now);
+        assert_eq!(age(0), "just now");
+        assert_eq!(age(1), "1 second ago");
+        assert_eq!(age(59 * 60 + 59), "59 minutes ago");
+        assert_eq!(age(2 * 24 * 3600), "2 days ago");
+        assert_eq!(age(3 * 365 * 24 * 3600 + 5), "3 years ago");
+        assert_eq!(
+            format_age(now + Duration::from_secs(1), now),
+            "in the future"
+        );
+        assert!(age(59 * 60 + 59).len() <= AGE_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn timestamps_are_formatted_as_utc_dates_and_times() {
+        use std::time::Duration;
+        let at = |secs| format_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(at(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(at(1_709_251_199), "2024-02-29 23:59:59 UTC");
+        assert_eq!(at(4_102_444_800), "2100-01-01 00:00:00 UTC");
+        assert_eq!(
+            format_timestamp(SystemTime::UNIX_EPOCH - Duration::from_secs(1)),
+            "1s before 1970"
+        );
+    }
+
+    #[test]
+    fn counts_are_formatted_with_a_metric_suffix() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1234), "1.2k");
+        assert_eq!(format_count(120_456), "120k");
+        assert_eq!(format_count(7_000_000), "7.0M");
+        assert!(format_count(u64::MAX).len() <= COUNT_COLUMN_WIDTH);
+    }
+
     #[test]
     fn fit_string_inputs() {
         assert_eq!(
@@ -0,0 +1,421 @@
+use crate::interactive::widgets::MarkMode;
+use crosstermion::input::{Event, Key};
+use dua::{delete_recursively, move_recursively, traverse::TreeIndex, DeletionProgress};
+#[cfg(feature = "archive")]
+use dua::{verify_archive, ArchiveFormat, ArchiveSummary, ArchiveWriter};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// What happens when the user asks to remove the marked entries.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RemovalMode {
+    /// Marked entries are removed from disk.
+    #[default]
+    Enabled,
+    /// Marked entries are removed from the tree as if they were removed from disk, which isn't touched.
+    DryRun,
+    /// Marked entries can't be removed.
+    ReadOnly,
+}
+
+/// An entry to be removed, as it was marked.
+pub struct DeletionItem {
+    pub index: TreeIndex,
+    pub path: PathBuf,
+    pub size: u128,
+}
+
+/// An entry that was moved to the trash bin during this session, so it can be restored.
+#[cfg(feature = "trash-move")]
+pub struct TrashedEntry {
+    /// The names of all entries from the top-level entry down to this one.
+    pub names: Vec<PathBuf>,
+    /// The path the entry was traversed with.
+    pub path: PathBuf,
+    /// The absolute path of the entry, as the trash bin remembers it.
+    pub original_path: PathBuf,
+}
+
+#[cfg(feature = "trash-move")]
+impl TrashedEntry {
+    /// Remember the entry at `path` with `names`, or return `None` if its absolute path can't be determined.
+    pub fn new(names: Vec<PathBuf>, path: PathBuf) -> Option<Self> {
+        // Determine the path like the trash bin does, which doesn't resolve the entry itself if it's a symlink.
+        let absolute = std::env::current_dir().ok()?.join(&path);
+        let original_path = absolute
+            .parent()?
+            .canonicalize()
+            .ok()?
+            .join(absolute.file_name()?);
+        Some(TrashedEntry {
+            names,
+            path,
+            original_path,
+        })
+    }
+}
+
+/// Move `entry` out of the trash bin back to where it was, using the most recently trashed item of `items`
+/// with its path.
+#[cfg(all(
+    feature = "trash-move",
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+pub fn restore_from_trash(
+    entry: &TrashedEntry,
+    items: &[trash::TrashItem],
+) -> Result<(), trash::Error> {
+    let item = items
+        .iter()
+        .filter(|item| item.original_path() == entry.original_path)
+        .max_by_key(|item| item.time_deleted)
+        .ok_or_else(|| trash::Error::Unknown {
+            description: format!("'{}' isn't in the trash bin", entry.path.display()),
+        })?;
+    trash::os_limited::restore_all(Some(item.clone()))
+}
+
+/// What happened on the worker thread of a [`DeletionJob`].
+pub enum DeletionEvent {
+    /// The entry at `index` is now being removed.
+    Started { index: TreeIndex },
+    /// The entry at `index` was removed entirely, or the amount of entries that couldn't be removed.
+    Removed {
+        index: TreeIndex,
+        result: Result<(), usize>,
+    },
+    /// The removal of the entry at `index` was cancelled, and parts of it may still exist.
+    Interrupted { index: TreeIndex },
+    /// The entry at `index` was added to the archive entirely, or the amount of entries that couldn't be added.
+    #[cfg(feature = "archive")]
+    Archived {
+        index: TreeIndex,
+        result: Result<(), usize>,
+    },
+    /// The archive at `path` was written and verified, or the reason it couldn't be.
+    /// The archived entries are only removed afterwards, and only if this succeeded.
+    #[cfg(feature = "archive")]
+    ArchiveWritten {
+        path: PathBuf,
+        result: Result<ArchiveSummary, String>,
+    },
+    /// All entries were handled, or the job was cancelled, and no more events will follow.
+    Finished,
+}
+
+/// The removal of marked entries on a worker thread, one entry at a time.
+pub struct DeletionJob {
+    pub mode: MarkMode,
+    /// If set, entries are only pretended to be removed.
+    pub dry_run: bool,
+    pub progress: Arc<DeletionProgress>,
+    /// The directory entries are moved into, or the archive they are written into.
+    pub destination: Option<PathBuf>,
+    /// The size of all entries to remove, as known to the tree.
+    pub total_bytes: u128,
+    /// The size of all entries that were removed entirely, as known to the tree.
+    pub completed_bytes: u128,
+    /// A message about the result of the job, to show once it's finished.
+    pub outcome: Option<String>,
+    /// The entry currently being removed with its size, and the amount of bytes removed before it.
+    current: Option<(TreeIndex, u128, u64)>,
+    sizes: Vec<(TreeIndex, u128)>,
+    events: Receiver<DeletionEvent>,
+}
+
+impl DeletionJob {
+    /// Start removing `items` in order on a new thread, using `threads` for each of them.
+    /// `destination` is the directory to move items into, and must be set if `mode` is [`MarkMode::Move`].
+    /// It's the path of the archive to write if `mode` is `MarkMode::Archive`.
+    /// If `dry_run` is set, all items are reported as removed without touching the disk.
+    /// If set, `wake_up` receives an event that does nothing whenever there is progress, to trigger a redraw.
+    pub fn start(
+        mode: MarkMode,
+        destination: Option<PathBuf>,
+        dry_run: bool,
+        items: Vec<DeletionItem>,
+        threads: usize,
+        wake_up: Option<Sender<Event>>,
+    ) -> Self {
+        let progress = Arc::new(DeletionProgress::default());
+        let (tx, events) = mpsc::channel();
+        let job = DeletionJob {
+            mode,
+            dry_run,
+            progress: Arc::clone(&progress),
+            destination: destination.clone(),
+            total_bytes: items.iter().map(|item| item.size).sum(),
+            completed_bytes: 0,
+            outcome: None,
+            current: None,
+            sizes: items.iter().map(|item| (item.index, item.size)).collect(),
+            events,
+        };
+        let worker = Worker {
+            dry_run,
+            threads,
+            progress,
+            events: tx,
+            wake_up,
+        };
+        std::thread::Builder::new()
+            .name("dua-delete-marked".into())
+            .spawn(move || {
+                match mode {
+                    #[cfg(feature = "archive")]
+                    MarkMode::Archive { remove_originals } => worker.archive_items(
+                        &destination.expect("a path to write the archive to"),
+                        remove_originals,
+                        items,
+                    ),
+                    _ => worker.remove_items(mode, destination.as_deref(), items),
+                }
+                worker.notify(DeletionEvent::Finished);
+            })
+            .expect("spawning a thread to work");
+        job
+    }
+
+    /// Stop removing entries as soon as possible.
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+
+    /// Return the next event if there is one, without blocking if `wait` is false.
+    pub fn next_event(&mut self, wait: bool) -> Option<DeletionEvent> {
+        let event = if wait {
+            self.events.recv().ok()
+        } else {
+            self.events.try_recv().ok()
+        };
+        match &event {
+            Some(DeletionEvent::Started { index }) => {
+                self.current = Some((*index, self.size_of(*index), self.progress.bytes_removed()));
+            }
+            Some(DeletionEvent::Removed { index, result }) => {
+                // Entries removed after they were archived were counted already, and weren't started again.
+                if result.is_ok() && self.current.map(|(idx, _, _)| idx) == Some(*index) {
+                    self.completed_bytes += self.size_of(*index);
+                }
+                self.current = None;
+            }
+            #[cfg(feature = "archive")]
+            Some(DeletionEvent::Archived { index, result }) => {
+                if result.is_ok() {
+                    self.completed_bytes += self.size_of(*index);
+                }
+                self.current = None;
+            }
+            #[cfg(feature = "archive")]
+            Some(DeletionEvent::ArchiveWritten { .. }) => {}
+            Some(DeletionEvent::Interrupted { .. }) | Some(DeletionEvent::Finished) => {
+                self.current = None;
+            }
+            None => {}
+        }
+        event
+    }
+
+    /// The amount of bytes reclaimed so far, including the parts of the entry currently being removed.
+    pub fn reclaimed_bytes(&self) -> u128 {
+        self.completed_bytes
+            + self.current.map_or(0, |(_, size, bytes_before)| {
+                ((self.progress.bytes_removed() - bytes_before) as u128).min(size)
+            })
+    }
+
+    fn size_of(&self, index: TreeIndex) -> u128 {
+        self.sizes
+            .iter()
+            .find(|(idx, _)| *idx == index)
+            .map_or(0, |(_, size)| *size)
+    }
+}
+
+impl Drop for DeletionJob {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// The state of the thread doing the work of a [`DeletionJob`].
+struct Worker {
+    dry_run: bool,
+    threads: usize,
+    progress: Arc<DeletionProgress>,
+    events: Sender<DeletionEvent>,
+    wake_up: Option<Sender<Event>>,
+}
+
+impl Worker {
+    fn notify(&self, event: DeletionEvent) {
+        self.events.send(event).ok();
+        self.wake_up();
+    }
+
+    fn wake_up(&self) {
+        if let Some(wake_up) = &self.wake_up {
+            wake_up.send(Event::Key(Key::Alt('\r'))).ok();
+        }
+    }
+
+    fn remove_items(&self, mode: MarkMode, destination: Option<&Path>, items: Vec<DeletionItem>) {
+        for DeletionItem { index, path, .. } in items {
+            if self.progress.is_cancelled() {
+                break;
+            }
+            self.notify(DeletionEvent::Started { index });
+            if !self.remove_item(index, &path, || {
+                remove(mode, &path, destination, self.threads, &self.progress)
+            }) {
+                break;
+            }
+        }
+    }
+
+    /// Remove the item at `path` using `work` unless this is a dry run, and return `false` if it was interrupted.
+    fn remove_item(
+        &self,
+        index: TreeIndex,
+        path: &Path,
+        work: impl FnOnce() -> Result<(), usize> + Send,
+    ) -> bool {
+        let result = if self.dry_run {
+            Ok(())
+        } else {
+            run_with_progress(work, || self.wake_up())
+        };
+        if self.progress.is_cancelled() && path.symlink_metadata().is_ok() {
+            self.notify(DeletionEvent::Interrupted { index });
+            return false;
+        }
+        self.notify(DeletionEvent::Removed { index, result });
+        true
+    }
+
+    /// Write all `items` into a new archive at `archive`, and delete them afterwards if `remove_originals` is set
+    /// and the archive could be verified.
+    #[cfg(feature = "archive")]
+    fn archive_items(&self, archive: &Path, remove_originals: bool, items: Vec<DeletionItem>) {
+        let result = if self.dry_run {
+            for item in &items {
+                self.notify(DeletionEvent::Started { index: item.index });
+                self.notify(DeletionEvent::Archived {
+                    index: item.index,
+                    result: Ok(()),
+                });
+            }
+            Ok(ArchiveSummary::default())
+        } else {
+            self.write_archive(archive, &items)
+        };
+        let verified = result.is_ok();
+        self.notify(DeletionEvent::ArchiveWritten {
+            path: archive.to_owned(),
+            result,
+        });
+        if !(verified && remove_originals) {
+            return;
+        }
+        for DeletionItem { index, path, .. } in items {
+            if self.progress.is_cancelled() {
+                break;
+            }
+            if !self.remove_item(index, &path, || {
+                delete_recursively(&path, self.threads, &self.progress)
+            }) {
+                break;
+            }
+        }
+    }
+
+    #[cfg(feature = "archive")]
+    fn write_archive(
+        &self,
+        archive: &Path,
+        items: &[DeletionItem],
+    ) -> Result<ArchiveSummary, String> {
+        let format = ArchiveFormat::from_path(archive)
+            .ok_or_else(|| "only .tar.zst and .tar.gz archives are supported".to_owned())?;
+        let mut writer = ArchiveWriter::create(archive, format).map_err(|err| err.to_string())?;
+        let mut num_errors = 0;
+        for item in items {
+            if self.progress.is_cancelled() {
+                break;
+            }
+            self.notify(DeletionEvent::Started { index: item.index });
+            let result = run_with_progress(
+                || writer.append(&item.path, &self.progress),
+                || self.wake_up(),
+            );
+            num_errors += result.err().unwrap_or(0);
+            self.notify(DeletionEvent::Archived {
+                index: item.index,
+                result,
+            });
+        }
+        if self.progress.is_cancelled() {
+            drop(writer);
+            std::fs::remove_file(archive).ok();
+            return Err("archiving was cancelled".into());
+        }
+        let summary = run_with_progress(|| writer.finish(), || self.wake_up())
+            .map_err(|err| err.to_string())?;
+        if num_errors != 0 {
+            return Err(format!("{num_errors} entries couldn't be archived"));
+        }
+        run_with_progress(
+            || verify_archive(archive, format, summary),
+            || self.wake_up(),
+        )
+        .map_err(|err| format!("the archive couldn't be verified: {err}"))?;
+        Ok(summary)
+    }
+}
+
+fn remove(
+    mode: MarkMode,
+    path: &Path,
+    destination: Option<&Path>,
+    threads: usize,
+    progress: &Arc<DeletionProgress>,
+) -> Result<(), usize> {
+    match mode {
+        MarkMode::Delete => delete_recursively(path, threads, progress),
+        MarkMode::Move => move_recursively(
+            path,
+            destination.expect("a destination to move to"),
+            threads,
+            progress,
+        ),
+        #[cfg(feature = "trash-move")]
+        MarkMode::Trash => trash::delete(path).map_err(|_| 1),
+        #[cfg(feature = "archive")]
+        MarkMode::Archive { .. } => unreachable!("archiving isn't a way of removing entries"),
+    }
+}
+
+/// Run `work` on its own thread and call `on_tick` regularly until it's done, returning its result.
+fn run_with_progress<T: Send>(work: impl FnOnce() -> T + Send, mut on_tick: impl FnMut()) -> T {
+    const TICK: Duration = Duration::from_millis(100);
+    std::thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel();
+        scope.spawn(move || tx.send(work()).ok());
+        loop {
+            match rx.recv_timeout(TICK) {
+                Ok(res) => return res,
+                Err(RecvTimeoutError::Timeout) => on_tick(),
+                Err(RecvTimeoutError::Disconnected) => panic!("the worker thread panicked"),
+            }
+        }
+    })
+}
@@ -1,8 +1,8 @@
 use crate::interactive::{
     sorted_entries,
-    widgets::{MainWindow, MainWindowProps},
-    ByteVisualization, CursorDirection, CursorMode, DisplayOptions, EntryDataBundle, MarkEntryMode,
-    SortMode,
+    widgets::{MainWindow, MainWindowProps, MarkMode},
+    ByteVisualization, CursorDirection, CursorMode, DeletionJob, DisplayOptions, EntriesFilter,
+    EntryDataBundle, MarkEntryMode, Prompt, RemovalMode, SortMode,
 };
 use anyhow::Result;
 use crosstermion::input::{input_channel, Event, Key};
@@ -10,7 +10,11 @@ use dua::{
     traverse::{Traversal, TreeIndex},
     WalkOptions, WalkResult,
 };
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+    sync::mpsc::Sender,
+};
 use tui::backend::Backend;
 use tui_react::Terminal;
 
@@ -18,7 +22,9 @@ use tui_react::Terminal;
 pub enum FocussedPane {
     #[default]
     Main,
+    Treemap,
     Help,
+    Details,
     Mark,
 }
 
@@ -32,6 +38,39 @@ pub struct AppState {
     pub focussed: FocussedPane,
     pub bookmarks: BTreeMap<TreeIndex, TreeIndex>,
     pub is_scanning: bool,
+    /// If set, the entries of expanded directories are shown right below them, indented.
+    pub tree_view: bool,
+    /// The directories whose entries are shown below them while `tree_view` is set.
+    pub expanded: HashSet<TreeIndex>,
+    /// If set, only entries of the current directory matching it are shown.
+    pub filter: Option<EntriesFilter>,
+    /// If set, the line of text the user is currently typing, which receives all keys.
+    pub prompt: Option<Prompt>,
+    /// The options to rescan entries with, set once the initial scan is done.
+    pub walk_options: Option<WalkOptions>,
+    /// If set, the traversal wasn't read from this filesystem, and its entries must not be acted on.
+    pub is_loaded: bool,
+    /// A previous traversal to compare entries with, to show how much they changed in size since.
+    pub baseline: Option<Traversal>,
+    /// The removal of marked entries, while it's running in the background.
+    pub deletion: Option<DeletionJob>,
+    /// Receives events that do nothing to trigger a redraw when work done in the background progresses.
+    pub wake_up: Option<Sender<Event>>,
+    /// What happens when the user asks to remove the marked entries.
+    pub removal: RemovalMode,
+    /// If set, marked entries are removed without asking for confirmation first, unless they are archived.
+    pub skip_confirmation: bool,
+    /// The directory marked entries were last chosen to be moved into.
+    pub move_destination: Option<PathBuf>,
+    /// The archive marked entries were last chosen to be written into.
+    #[cfg(feature = "archive")]
+    pub archive_path: Option<PathBuf>,
+    /// All entries that would have been removed if this wasn't a dry run, in order, along with the directory
+    /// they would have been moved into or the archive they would have been written into.
+    pub dry_run_removals: Vec<(MarkMode, PathBuf, Option<PathBuf>)>,
+    /// All entries that were moved to the trash bin and weren't restored yet, in order.
+    #[cfg(feature = "trash-move")]
+    pub trashed: Vec<crate::interactive::TrashedEntry>,
 }
 
 pub enum ProcessingResult {
@@ -72,6 +111,7 @@ impl AppState {
         use crosstermion::input::Key::*;
         use FocussedPane::*;
 
+        self.poll_deletion(window, traversal);
         self.draw(window, traversal, *display, terminal)?;
         for event in events {
             let key = match event {
@@ -80,16 +120,27 @@ impl AppState {
             };
 
             self.reset_message();
+            self.poll_deletion(window, traversal);
+            if self.prompt.is_some() && !matches!(key, Ctrl('c')) {
+                self.process_prompt_key(key, window, traversal);
+                self.draw(window, traversal, *display, terminal)?;
+                continue;
+            }
             match key {
                 Char('?') => self.toggle_help_pane(window),
+                Char('i') => self.toggle_details_pane(window),
                 Char('\t') => {
                     self.cycle_focus(window);
                 }
+                Ctrl('x') => self.cancel_deletion(),
                 Ctrl('c') => {
                     return Ok(ProcessingResult::ExitRequested(WalkResult {
                         num_errors: traversal.io_errors,
                     }))
                 }
+                Esc if matches!(self.focussed, Main) && self.filter.is_some() => {
+                    self.set_filter(None, traversal)
+                }
                 Char('q') | Esc => match self.focussed {
                     Main => {
                         return Ok(ProcessingResult::ExitRequested(WalkResult {
@@ -101,12 +152,21 @@ impl AppState {
                         self.focussed = Main;
                         window.help_pane = None
                     }
+                    Treemap => {
+                        self.focussed = Main;
+                        window.treemap = None
+                    }
+                    Details => {
+                        self.focussed = Main;
+                        window.details_pane = None
+                    }
                 },
                 _ => {}
             }
 
             match self.focussed {
-                Mark => self.dispatch_to_mark_pane(key, window, traversal, *display, terminal),
+                Mark => self.dispatch_to_mark_pane(key, window, traversal),
+                Treemap => self.dispatch_to_treemap(key, window, traversal),
                 Help => {
                     window
                         .help_pane
@@ -114,6 +174,13 @@ impl AppState {
                         .expect("help pane")
                         .process_events(key);
                 }
+                Details => {
+                    window
+                        .details_pane
+                        .as_mut()
+                        .expect("details pane")
+                        .process_events(key);
+                }
                 Main => match key {
                     Char('O') => self.open_that(traversal),
                     Char(' ') => self.mark_entry(
@@ -135,6 +202,16 @@ impl AppState {
                         traversal,
                     ),
                     Char('a') => self.mark_all_entries(MarkEntryMode::Toggle, window, traversal),
+                    Char('u') | Char('h') | Backspace | Left if self.tree_view => {
+                        self.collapse_selected_entry(traversal)
+                    }
+                    Char(digit @ '1'..='9') => self.exit_nodes_with_traversal(
+                        digit.to_digit(10).expect("a digit") as usize,
+                        traversal,
+                    ),
+                    Char('o') | Char('l') | Char('\n') | Right if self.tree_view => {
+                        self.expand_selected_entry(traversal)
+                    }
                     Char('u') | Char('h') | Backspace | Left => {
                         self.exit_node_with_traversal(traversal)
                     }
@@ -147,8 +224,23 @@ impl AppState {
                     Char('k') | Up => self.change_entry_selection(CursorDirection::Up),
                     Char('j') | Down => self.change_entry_selection(CursorDirection::Down),
                     Ctrl('d') | PageDown => self.change_entry_selection(CursorDirection::PageDown),
+                    #[cfg(feature = "trash-move")]
+                    Ctrl('z') => self.restore_trashed_entries(window, traversal),
+                    Char('r') => self.rescan_selected_entry(window, traversal, *display, terminal),
+                    Char('R') => self.rescan_all_entries(window, traversal, *display, terminal),
+                    Char('/') => self.open_search_prompt(),
+                    Char(':') => self.open_go_to_prompt(),
+                    Char('n') => self.select_next_match(true),
+                    Char('N') => self.select_next_match(false),
                     Char('s') => self.cycle_sorting(traversal),
+                    Char('A') => self.cycle_name_sorting(traversal),
+                    Char('c') => self.cycle_count_sorting(traversal),
+                    Char('m') => self.cycle_mtime_sorting(traversal),
                     Char('g') => display.byte_vis.cycle(),
+                    Char('M') => display.show_mtime = !display.show_mtime,
+                    Char('C') => display.show_entry_count = !display.show_entry_count,
+                    Char('t') => self.toggle_tree_view(traversal),
+                    Char('T') => self.toggle_treemap(window),
                     _ => {}
                 },
             };
@@ -183,6 +275,8 @@ pub struct TerminalApp {
 }
 
 type KeyboardInputAndApp = (std::sync::mpsc::Receiver<Event>, TerminalApp);
+/// All input events, and a way to add events to them, if there is any input.
+type KeysAndWakeUp = (std::sync::mpsc::Receiver<Event>, Option<Sender<Event>>);
 
 impl TerminalApp {
     pub fn refresh_view<B>(&mut self, terminal: &mut Terminal<B>)
@@ -215,31 +309,98 @@ impl TerminalApp {
             terminal,
             events,
         )? {
-            ProcessingResult::Finished(res) | ProcessingResult::ExitRequested(res) => Ok(res),
+            ProcessingResult::Finished(res) => Ok(res),
+            ProcessingResult::ExitRequested(res) => {
+                // Don't leave a partially removed entry behind without knowing about it.
+                self.state
+                    .finish_deletion(&mut self.window, &mut self.traversal);
+                Ok(res)
+            }
         }
     }
 
-    pub fn initialize<B>(
+    /// Create an app from an existing `traversal`, for example one loaded from a snapshot, without touching the filesystem.
+    ///
+    /// The entries of such a traversal may not exist on this machine, or be entirely different ones, so they can't be
+    /// removed or rescanned.
+    pub fn initialize_with_traversal<B>(
         terminal: &mut Terminal<B>,
         options: WalkOptions,
-        input_paths: Vec<PathBuf>,
+        traversal: Traversal,
         mode: Interaction,
-    ) -> Result<Option<KeyboardInputAndApp>>
+    ) -> Result<KeyboardInputAndApp>
+    where
+        B: Backend,
+    {
+        let (display, window, (keys_rx, wake_up)) = Self::prepare(terminal, options, mode)?;
+        let sorting = Default::default();
+        let root = traversal.root_index;
+        let entries = sorted_entries(&traversal.tree, root, sorting);
+        let mut app = TerminalApp {
+            state: AppState {
+                root,
+                sorting,
+                selected: entries.first().map(|b| b.index),
+                entries,
+                is_loaded: true,
+                removal: RemovalMode::ReadOnly,
+                wake_up,
+                ..Default::default()
+            },
+            display,
+            traversal,
+            window,
+        };
+        app.refresh_view(terminal);
+        Ok((keys_rx, app))
+    }
+
+    fn prepare<B>(
+        terminal: &mut Terminal<B>,
+        options: WalkOptions,
+        mode: Interaction,
+    ) -> Result<(DisplayOptions, MainWindow, KeysAndWakeUp)>
     where
         B: Backend,
     {
         terminal.hide_cursor()?;
         terminal.clear()?;
-        let mut display: DisplayOptions = options.clone().into();
+        let mut display: DisplayOptions = options.into();
         display.byte_vis = ByteVisualization::PercentageAndBar;
-        let mut window = MainWindow::default();
-        let keys_rx = match mode {
+        let input = match mode {
             Interaction::None => {
                 let (_, keys_rx) = std::sync::mpsc::channel();
-                keys_rx
+                (keys_rx, None)
+            }
+            Interaction::Full => {
+                // Forward all input into a channel that background work can send events to as well.
+                let (tx, keys_rx) = std::sync::mpsc::channel();
+                let input = input_channel();
+                let input_tx = tx.clone();
+                std::thread::spawn(move || {
+                    for event in input {
+                        if input_tx.send(event).is_err() {
+                            break;
+                        }
+                    }
+                });
+                (keys_rx, Some(tx))
             }
-            Interaction::Full => input_channel(),
         };
+        Ok((display, MainWindow::default(), input))
+    }
+
+    pub fn initialize<B>(
+        terminal: &mut Terminal<B>,
+        options: WalkOptions,
+        input_paths: Vec<PathBuf>,
+        mode: Interaction,
+    ) -> Result<Option<KeyboardInputAndApp>>
+    where
+        B: Backend,
+    {
+        let (mut display, mut window, (keys_rx, wake_up)) =
+            Self::prepare(terminal, options.clone(), mode)?;
 
         let fetch_buffered_key_events = || {
             let mut keys = Vec::new();
@@ -251,12 +412,12 @@ impl TerminalApp {
 
         let mut state = None::<AppState>;
         let mut received_events = false;
-        let traversal = Traversal::from_walk(options, input_paths, |traversal| {
+        let traversal = Traversal::from_walk(options.clone(), input_paths, |traversal| {
             let s = match state.as_mut() {
                 Some(s) => {
-                    s.entries = sorted_entries(&traversal.tree, s.root, s.sorting);
+                    s.refresh_entries(&traversal.tree);
                     if !received_events {
-                        s.selected = s.entries.get(0).map(|b| b.index);
+                        s.selected = s.entries.first().map(|b| b.index);
                     }
                     s
                 }
@@ -268,7 +429,7 @@ impl TerminalApp {
                         AppState {
                             root: traversal.root_index,
                             sorting,
-                            selected: entries.get(0).map(|b| b.index),
+                            selected: entries.first().map(|b| b.index),
                             entries,
                             is_scanning: true,
                             ..Default::default()
@@ -313,11 +474,13 @@ impl TerminalApp {
                         }
                     });
                     s.is_scanning = false;
-                    s.entries = sorted_entries(&traversal.tree, s.root, s.sorting);
+                    s.walk_options = Some(options);
+                    s.wake_up = wake_up;
+                    s.refresh_entries(&traversal.tree);
                     s.selected = if received_events {
-                        s.selected.or_else(|| s.entries.get(0).map(|b| b.index))
+                        s.selected.or_else(|| s.entries.first().map(|b| b.index))
                     } else {
-                        s.entries.get(0).map(|b| b.index)
+                        s.entries.first().map(|b| b.index)
                     };
                     s
                 },
@@ -1,14 +1,20 @@
 use crate::interactive::{
-    app::FocussedPane::*,
-    path_of, sorted_entries,
-    widgets::{HelpPane, MainWindow, MarkMode, MarkPane},
-    AppState, DisplayOptions, EntryDataBundle,
+    app::{FocussedPane, FocussedPane::*},
+    names_of, node_by_names, node_by_path, path_of, sorted_and_filtered_entries, sorted_entries,
+    widgets::{
+        DetailsPane, HelpPane, MainWindow, MarkMode, MarkPane, TreemapDirection, TreemapPane,
+    },
+    with_expanded_entries, AppState, DeletionEvent, DeletionItem, DeletionJob, DisplayOptions,
+    EntriesFilter, EntryDataBundle, Prompt, PromptEvent, PromptKind, RemovalMode,
 };
 use crosstermion::input::Key;
-use dua::traverse::{Traversal, TreeIndex};
+use dua::traverse::{EntryType, Traversal, Tree, TreeIndex};
 use itertools::Itertools;
 use petgraph::{visit::Bfs, Direction};
-use std::{fs, io, path::PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 use tui::backend::Backend;
 use tui_react::Terminal;
 
@@ -48,10 +54,30 @@ impl CursorDirection {
 }
 
 impl AppState {
-    pub fn open_that(&self, traversal: &Traversal) {
+    pub fn open_that(&mut self, traversal: &mut Traversal) {
         if let Some(idx) = self.selected {
-            open::that(path_of(&traversal.tree, idx)).ok();
+            if self.check_existence(idx, traversal) {
+                open::that(path_of(&traversal.tree, idx)).ok();
+            } else {
+                self.message = Some("The entry doesn't exist anymore".into());
+            }
+        }
+    }
+
+    /// Return whether the entry at `index` still exists on disk, and remember in the tree if it doesn't, as
+    /// it's only known whether it existed during the traversal otherwise.
+    ///
+    /// Entries of loaded traversals aren't checked, as their paths may refer to another machine.
+    fn check_existence(&mut self, index: TreeIndex, traversal: &mut Traversal) -> bool {
+        if self.is_loaded || traversal.tree.node_weight(index).is_none() {
+            return true;
+        }
+        let exists = path_of(&traversal.tree, index).symlink_metadata().is_ok();
+        if !exists {
+            traversal.tree[index].entry_type = EntryType::Missing;
+            self.refresh_entries(&traversal.tree);
         }
+        exists
     }
 
     pub fn exit_node_with_traversal(&mut self, traversal: &Traversal) {
@@ -59,6 +85,22 @@ impl AppState {
         self.exit_node(entries);
     }
 
+    /// Leave the current directory and `levels - 1` of the directories containing it, or as many as there are.
+    pub fn exit_nodes_with_traversal(&mut self, levels: usize, traversal: &Traversal) {
+        for level in 0..levels {
+            if level != 0
+                && traversal
+                    .tree
+                    .neighbors_directed(self.root, Direction::Incoming)
+                    .next()
+                    .is_none()
+            {
+                break;
+            }
+            self.exit_node_with_traversal(traversal);
+        }
+    }
+
     fn entries_for_exit_node(
         &self,
         traversal: &Traversal,
@@ -70,7 +112,10 @@ impl AppState {
             .map(|parent_idx| {
                 (
                     parent_idx,
-                    sorted_entries(&traversal.tree, parent_idx, self.sorting),
+                    self.with_expanded_entries(
+                        &traversal.tree,
+                        sorted_entries(&traversal.tree, parent_idx, self.sorting),
+                    ),
                 )
             })
     }
@@ -78,13 +123,14 @@ impl AppState {
     pub fn exit_node(&mut self, entries: Option<(TreeIndex, Vec<EntryDataBundle>)>) {
         match entries {
             Some((parent_idx, entries)) => {
+                self.filter = None;
                 self.root = parent_idx;
                 self.entries = entries;
                 self.selected = self
                     .bookmarks
                     .get(&parent_idx)
                     .copied()
-                    .or_else(|| self.entries.get(0).map(|b| b.index));
+                    .or_else(|| self.entries.first().map(|b| b.index));
             }
             None => self.message = Some("Top level reached".into()),
         }
@@ -122,6 +168,7 @@ impl AppState {
             ) {
                 Some(b) => {
                     self.bookmarks.insert(self.root, previously_selected);
+                    self.filter = None;
                     self.root = previously_selected;
                     self.selected = Some(b.index);
                     self.entries = new_entries;
@@ -153,12 +200,360 @@ impl AppState {
 
     pub fn cycle_sorting(&mut self, traversal: &Traversal) {
         self.sorting.toggle_size();
-        self.entries = sorted_entries(&traversal.tree, self.root, self.sorting);
+        self.refresh_entries(&traversal.tree);
+    }
+
+    pub fn cycle_name_sorting(&mut self, traversal: &Traversal) {
+        self.sorting.toggle_name();
+        self.refresh_entries(&traversal.tree);
+    }
+
+    pub fn cycle_count_sorting(&mut self, traversal: &Traversal) {
+        self.sorting.toggle_count();
+        self.refresh_entries(&traversal.tree);
+    }
+
+    pub fn cycle_mtime_sorting(&mut self, traversal: &Traversal) {
+        self.sorting.toggle_mtime();
+        self.refresh_entries(&traversal.tree);
+    }
+
+    /// Set the entries of the current directory, sorted and filtered, along with the ones of expanded directories.
+    pub fn refresh_entries(&mut self, tree: &Tree) {
+        let entries =
+            sorted_and_filtered_entries(tree, self.root, self.sorting, self.filter.as_ref());
+        self.entries = self.with_expanded_entries(tree, entries);
+    }
+
+    /// Return `entries` along with the ones of expanded directories if entries are shown as a tree.
+    fn with_expanded_entries(
+        &self,
+        tree: &Tree,
+        entries: Vec<EntryDataBundle>,
+    ) -> Vec<EntryDataBundle> {
+        if self.tree_view {
+            with_expanded_entries(tree, entries, self.sorting, &self.expanded)
+        } else {
+            entries
+        }
+    }
+
+    /// Switch between showing only the entries of the current directory, and showing them as a tree.
+    pub fn toggle_tree_view(&mut self, traversal: &Traversal) {
+        self.tree_view = !self.tree_view;
+        self.refresh_entries(&traversal.tree);
+        // Entries of expanded directories may not be shown anymore, so select the one containing them instead.
+        let mut selected = self.selected;
+        while let Some(idx) = selected.filter(|idx| !self.entries.iter().any(|e| e.index == *idx)) {
+            selected = traversal
+                .tree
+                .neighbors_directed(idx, Direction::Incoming)
+                .next();
+        }
+        self.selected = selected.or_else(|| self.entries.first().map(|e| e.index));
+    }
+
+    /// Show the entries of the selected directory below it, or select its first entry if they are shown already.
+    pub fn expand_selected_entry(&mut self, traversal: &Traversal) {
+        let selected = match self.selected {
+            Some(selected) => selected,
+            None => return,
+        };
+        if self.expanded.contains(&selected) {
+            self.change_entry_selection(CursorDirection::Down);
+        } else if traversal
+            .tree
+            .neighbors_directed(selected, Direction::Outgoing)
+            .next()
+            .is_some()
+        {
+            self.expanded.insert(selected);
+            self.refresh_entries(&traversal.tree);
+        } else {
+            self.message = Some("Entry is a file or an empty directory".into());
+        }
+    }
+
+    /// Hide the entries of the selected directory, or select the directory containing the selected entry.
+    /// Leave the current directory if the selected entry is in it.
+    pub fn collapse_selected_entry(&mut self, traversal: &Traversal) {
+        let selected = match self.selected {
+            Some(selected) => selected,
+            None => return self.exit_node_with_traversal(traversal),
+        };
+        if self.expanded.remove(&selected) {
+            self.refresh_entries(&traversal.tree);
+            return;
+        }
+        match traversal
+            .tree
+            .neighbors_directed(selected, Direction::Incoming)
+            .next()
+        {
+            Some(parent_idx) if parent_idx != self.root => {
+                self.selected = Some(parent_idx);
+                self.bookmarks.insert(self.root, parent_idx);
+            }
+            _ => self.exit_node_with_traversal(traversal),
+        }
+    }
+
+    pub fn open_search_prompt(&mut self) {
+        let pattern = self
+            .filter
+            .as_ref()
+            .map(|f| f.pattern.as_str())
+            .unwrap_or("");
+        self.prompt = Some(Prompt::new(PromptKind::Search, pattern));
+    }
+
+    pub fn open_go_to_prompt(&mut self) {
+        self.prompt = Some(Prompt::new(PromptKind::GoTo, ""));
+    }
+
+    pub fn process_prompt_key(&mut self, key: Key, window: &mut MainWindow, traversal: &Traversal) {
+        let prompt = match self.prompt.as_mut() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        match (prompt.kind, prompt.process_key(key)) {
+            (_, PromptEvent::Ignored) => {}
+            (PromptKind::Search, PromptEvent::Changed) => {
+                let filter = (!prompt.input.is_empty()).then(|| EntriesFilter::new(&prompt.input));
+                self.set_filter(filter, traversal);
+            }
+            (PromptKind::Search, PromptEvent::Submitted) => self.prompt = None,
+            (PromptKind::Search, PromptEvent::Cancelled) => {
+                self.prompt = None;
+                self.set_filter(None, traversal);
+            }
+            (PromptKind::ConfirmRemoval(_), PromptEvent::Changed) => {}
+            (PromptKind::ConfirmRemoval(mode), PromptEvent::Submitted) => {
+                let input = prompt.input.trim().to_owned();
+                self.prompt = None;
+                let num_marked = window.mark_pane.as_ref().map_or(0, |p| p.marked().len());
+                let mode = match mode {
+                    #[cfg(feature = "archive")]
+                    MarkMode::Archive { .. } if input == "delete" => Some(MarkMode::Archive {
+                        remove_originals: true,
+                    }),
+                    _ if input == "y" || input == num_marked.to_string() => Some(mode),
+                    _ => None,
+                };
+                match (window.mark_pane.as_ref(), mode) {
+                    (Some(pane), Some(mode)) => self.start_deletion(mode, pane, traversal),
+                    _ => {
+                        self.message = Some(format!(
+                            "Nothing was removed - type 'y' or '{num_marked}' to confirm"
+                        ))
+                    }
+                }
+            }
+            (PromptKind::MoveDestination, PromptEvent::Changed) => {}
+            (PromptKind::MoveDestination, PromptEvent::Submitted) => {
+                let destination = PathBuf::from(prompt.input.trim());
+                self.prompt = None;
+                if !destination.is_dir() {
+                    self.message = Some(format!(
+                        "Nothing was moved - '{}' is not a directory",
+                        destination.display()
+                    ));
+                    return;
+                }
+                self.move_destination = Some(destination);
+                if let Some(pane) = window.mark_pane.as_ref() {
+                    self.confirm_deletion(MarkMode::Move, pane, traversal);
+                }
+            }
+            #[cfg(feature = "archive")]
+            (PromptKind::ArchiveDestination, PromptEvent::Changed) => {}
+            #[cfg(feature = "archive")]
+            (PromptKind::ArchiveDestination, PromptEvent::Submitted) => {
+                let path = PathBuf::from(prompt.input.trim());
+                self.prompt = None;
+                if let Some(pane) = window.mark_pane.as_ref() {
+                    match check_archive_path(&path, pane) {
+                        Ok(()) => {
+                            self.archive_path = Some(path);
+                            self.confirm_deletion(
+                                MarkMode::Archive {
+                                    remove_originals: false,
+                                },
+                                pane,
+                                traversal,
+                            );
+                        }
+                        Err(reason) => {
+                            self.message = Some(format!("Nothing was archived - {reason}"))
+                        }
+                    }
+                }
+            }
+            #[cfg(feature = "archive")]
+            (PromptKind::ArchiveDestination, PromptEvent::Cancelled) => {
+                self.prompt = None;
+                self.message = Some("Nothing was archived".into());
+            }
+            (PromptKind::MoveDestination, PromptEvent::Cancelled)
+            | (PromptKind::ConfirmRemoval(_), PromptEvent::Cancelled) => {
+                self.prompt = None;
+                self.message = Some("Nothing was removed".into());
+            }
+            (PromptKind::GoTo, PromptEvent::Changed) => {}
+            (PromptKind::GoTo, PromptEvent::CompletionRequested) => {
+                self.complete_go_to_path(traversal)
+            }
+            (PromptKind::GoTo, PromptEvent::Submitted) => {
+                let input = prompt.input.trim().to_owned();
+                self.prompt = None;
+                if !input.is_empty() {
+                    self.go_to_path(&input, traversal);
+                }
+            }
+            (PromptKind::GoTo, PromptEvent::Cancelled) => self.prompt = None,
+            (_, PromptEvent::CompletionRequested) => {}
+        }
+    }
+
+    /// Complete the last component of the path in the go-to prompt with the names of the entries in its directory,
+    /// as far as they have the same beginning.
+    fn complete_go_to_path(&mut self, traversal: &Traversal) {
+        let prompt = match self.prompt.as_mut() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        let tree = &traversal.tree;
+        let (dir, partial) = match prompt.input.rfind('/') {
+            Some(pos) => prompt.input.split_at(pos + 1),
+            None => ("", prompt.input.as_str()),
+        };
+        let (dir, partial) = (dir.to_owned(), partial.to_owned());
+        let dir_idx = if dir.is_empty() {
+            Some(self.root)
+        } else {
+            node_by_path(tree, self.root, traversal.root_index, Path::new(&dir))
+        };
+        let dir_idx = match dir_idx {
+            Some(idx) => idx,
+            None => {
+                self.message = Some(format!("No directory at '{dir}'"));
+                return;
+            }
+        };
+        let candidates: Vec<_> = tree
+            .neighbors_directed(dir_idx, Direction::Outgoing)
+            .map(|idx| (idx, tree[idx].name.to_string_lossy().into_owned()))
+            .filter(|(_, name)| name.starts_with(&partial))
+            .sorted_by(|(_, lhs), (_, rhs)| lhs.cmp(rhs))
+            .collect();
+        match candidates.as_slice() {
+            [] => self.message = Some(format!("No entry in '{dir}' starts with '{partial}'")),
+            [(idx, name)] => {
+                let has_entries = tree
+                    .neighbors_directed(*idx, Direction::Outgoing)
+                    .next()
+                    .is_some();
+                prompt.input = format!("{dir}{name}{}", if has_entries { "/" } else { "" });
+            }
+            [(_, first), rest @ ..] => {
+                let common = rest.iter().fold(first.as_str(), |common, (_, name)| {
+                    let len = common
+                        .chars()
+                        .zip(name.chars())
+                        .take_while(|(lhs, rhs)| lhs == rhs)
+                        .map(|(c, _)| c.len_utf8())
+                        .sum();
+                    &common[..len]
+                });
+                prompt.input = format!("{dir}{common}");
+                self.message = Some(candidates.iter().map(|(_, name)| name).join("  "));
+            }
+        }
+    }
+
+    /// Make the entry at `input` the current directory, or select it in its directory if it has no entries.
+    /// All directories on the way to it remember which of their entries leads there.
+    fn go_to_path(&mut self, input: &str, traversal: &Traversal) {
+        let tree = &traversal.tree;
+        let target = match node_by_path(tree, self.root, traversal.root_index, Path::new(input)) {
+            Some(idx) => idx,
+            None => {
+                self.message = Some(format!("No entry at '{input}'"));
+                return;
+            }
+        };
+        let has_entries = tree
+            .neighbors_directed(target, Direction::Outgoing)
+            .next()
+            .is_some();
+        let (root, selected) = match tree.neighbors_directed(target, Direction::Incoming).next() {
+            Some(parent_idx) if !has_entries => (parent_idx, Some(target)),
+            _ => (target, None),
+        };
+        let mut child_idx = root;
+        while let Some(parent_idx) = tree
+            .neighbors_directed(child_idx, Direction::Incoming)
+            .next()
+        {
+            self.bookmarks.insert(parent_idx, child_idx);
+            child_idx = parent_idx;
+        }
+        if let Some(selected) = selected {
+            self.bookmarks.insert(root, selected);
+        }
+        self.set_root(root, traversal);
+        self.selected = self
+            .bookmarks
+            .get(&root)
+            .copied()
+            .filter(|idx| self.entries.iter().any(|e| e.index == *idx))
+            .or_else(|| self.entries.first().map(|e| e.index));
+    }
+
+    /// Show only entries of the current directory matching `filter`, keeping the selection if it still matches.
+    pub fn set_filter(&mut self, filter: Option<EntriesFilter>, traversal: &Traversal) {
+        self.filter = filter;
+        self.refresh_entries(&traversal.tree);
+        if !self.entries.iter().any(|e| Some(e.index) == self.selected) {
+            self.selected = self.entries.first().map(|e| e.index);
+        }
+    }
+
+    /// Select the next or previous entry matching the filter, starting over at the other end of the list.
+    pub fn select_next_match(&mut self, forward: bool) {
+        if self.filter.is_none() {
+            self.message = Some("Press '/' to search".into());
+            return;
+        }
+        let num_entries = self.entries.len();
+        if num_entries == 0 {
+            return;
+        }
+        let position = self
+            .selected
+            .and_then(|selected| self.entries.iter().position(|e| e.index == selected));
+        let next_position = match (position, forward) {
+            (None, _) => 0,
+            (Some(position), true) => (position + 1) % num_entries,
+            (Some(position), false) => (position + num_entries - 1) % num_entries,
+        };
+        self.selected = Some(self.entries[next_position].index);
+        self.bookmarks
+            .insert(self.root, self.entries[next_position].index);
     }
 
     pub fn reset_message(&mut self) {
         if self.is_scanning {
             self.message = Some("-> scanning <-".into());
+        } else if let Some(job) = &self.deletion {
+            self.message = Some(match job.mode {
+                _ if job.dry_run => "-> pretending to remove marked entries (dry run) <-".into(),
+                #[cfg(feature = "archive")]
+                MarkMode::Archive { .. } => {
+                    "-> archiving marked entries <- Ctrl + x to cancel".into()
+                }
+                _ => "-> removing marked entries <- Ctrl + x to cancel".into(),
+            });
         } else {
             self.message = None;
         }
@@ -166,7 +561,7 @@ impl AppState {
 
     pub fn toggle_help_pane(&mut self, window: &mut MainWindow) {
         self.focussed = match self.focussed {
-            Main | Mark => {
+            Main | Treemap | Details | Mark => {
                 window.help_pane = Some(HelpPane::default());
                 Help
             }
@@ -176,82 +571,99 @@ impl AppState {
             }
         }
     }
+
+    /// Show the details of the selected entry next to the entries, or hide them, keeping the focus on the entries
+    /// so the selection can be changed while they are shown.
+    pub fn toggle_details_pane(&mut self, window: &mut MainWindow) {
+        if window.details_pane.take().is_none() {
+            window.details_pane = Some(DetailsPane::default());
+        } else if matches!(self.focussed, Details) {
+            self.focussed = Main;
+        }
+    }
+
+    /// Show the entries of the current directory as treemap below them, or hide it.
+    pub fn toggle_treemap(&mut self, window: &mut MainWindow) {
+        if window.treemap.take().is_none() {
+            window.treemap = Some(TreemapPane::default());
+        } else if matches!(self.focussed, Treemap) {
+            self.focussed = Main;
+        }
+    }
+
+    /// Select the entry next to the selected one in the treemap, or enter or leave directories like in the list.
+    pub fn dispatch_to_treemap(
+        &mut self,
+        key: Key,
+        window: &mut MainWindow,
+        traversal: &Traversal,
+    ) {
+        use crosstermion::input::Key::*;
+        let direction = match key {
+            Char('h') | Left => TreemapDirection::Left,
+            Char('l') | Right => TreemapDirection::Right,
+            Char('k') | Up => TreemapDirection::Up,
+            Char('j') | Down => TreemapDirection::Down,
+            Char('o') | Char('\n') => return self.enter_node_with_traversal(traversal),
+            Char('u') | Backspace => return self.exit_node_with_traversal(traversal),
+            Char('T') => return self.toggle_treemap(window),
+            _ => return,
+        };
+        if let Some(index) = window
+            .treemap
+            .as_ref()
+            .and_then(|treemap| treemap.neighbour(self.selected, direction))
+        {
+            self.selected = Some(index);
+            self.bookmarks.insert(self.root, index);
+        }
+    }
+
     pub fn cycle_focus(&mut self, window: &mut MainWindow) {
         if let Some(p) = window.mark_pane.as_mut() {
             p.set_focus(false)
         };
-        self.focussed = match (self.focussed, &window.help_pane, &mut window.mark_pane) {
-            (Main, Some(_), _) => Help,
-            (Help, _, Some(ref mut pane)) => {
-                pane.set_focus(true);
-                Mark
-            }
-            (Help, _, None) => Main,
-            (Mark, _, _) => Main,
-            (Main, None, None) => Main,
-            (Main, None, Some(ref mut pane)) => {
-                pane.set_focus(true);
-                Mark
-            }
+        let is_open = |pane: FocussedPane| match pane {
+            Main => true,
+            Treemap => window.treemap.is_some(),
+            Help => window.help_pane.is_some(),
+            Details => window.details_pane.is_some(),
+            Mark => window.mark_pane.is_some(),
+        };
+        let next = |pane: FocussedPane| match pane {
+            Main => Treemap,
+            Treemap => Help,
+            Help => Details,
+            Details => Mark,
+            Mark => Main,
         };
+        let mut focussed = next(self.focussed);
+        while !is_open(focussed) {
+            focussed = next(focussed);
+        }
+        if let (Mark, Some(pane)) = (focussed, window.mark_pane.as_mut()) {
+            pane.set_focus(true);
+        }
+        self.focussed = focussed;
     }
 
-    pub fn dispatch_to_mark_pane<B>(
+    pub fn dispatch_to_mark_pane(
         &mut self,
         key: Key,
         window: &mut MainWindow,
-        traversal: &mut Traversal,
-        display: DisplayOptions,
-        terminal: &mut Terminal<B>,
-    ) where
-        B: Backend,
-    {
-        let res = window.mark_pane.take().and_then(|p| p.process_events(key));
+        traversal: &Traversal,
+    ) {
+        let allow_removal = self.removal != RemovalMode::ReadOnly;
+        let res = window
+            .mark_pane
+            .take()
+            .and_then(|p| p.process_events(key, allow_removal));
         window.mark_pane = match res {
-            Some((pane, mode)) => match mode {
-                Some(MarkMode::Delete) => {
-                    self.message = Some("Deleting entries...".to_string());
-                    let mut entries_deleted = 0;
-                    let res = pane.iterate_deletable_items(|mut pane, entry_to_delete| {
-                        window.mark_pane = Some(pane);
-                        self.draw(window, traversal, display, terminal).ok();
-                        pane = window.mark_pane.take().expect("option to be filled");
-                        match self.delete_entry(entry_to_delete, traversal) {
-                            Ok(ed) => {
-                                entries_deleted += ed;
-                                self.message =
-                                    Some(format!("Deleted {} entries...", entries_deleted));
-                                Ok(pane)
-                            }
-                            Err(c) => Err((pane, c)),
-                        }
-                    });
-                    self.message = None;
-                    res
-                }
-                #[cfg(feature = "trash-move")]
-                Some(MarkMode::Trash) => {
-                    self.message = Some("Trashing entries...".to_string());
-                    let mut entries_trashed = 0;
-                    let res = pane.iterate_deletable_items(|mut pane, entry_to_trash| {
-                        window.mark_pane = Some(pane);
-                        self.draw(window, traversal, display, terminal).ok();
-                        pane = window.mark_pane.take().expect("option to be filled");
-                        match self.trash_entry(entry_to_trash, traversal) {
-                            Ok(ed) => {
-                                entries_trashed += ed;
-                                self.message =
-                                    Some(format!("Trashed {} entries...", entries_trashed));
-                                Ok(pane)
-                            }
-                            Err(c) => Err((pane, c)),
-                        }
-                    });
-                    self.message = None;
-                    res
-                }
-                None => Some(pane),
-            },
+            Some((pane, Some(mode))) => {
+                self.request_deletion(mode, &pane, traversal);
+                Some(pane)
+            }
+            Some((pane, None)) => Some(pane),
             None => None,
         };
         if window.mark_pane.is_none() {
@@ -259,35 +671,354 @@ impl AppState {
         }
     }
 
-    pub fn delete_entry(
-        &mut self,
-        index: TreeIndex,
-        traversal: &mut Traversal,
-    ) -> Result<usize, usize> {
-        let mut entries_deleted = 0;
-        if let Some(_entry) = traversal.tree.node_weight(index) {
-            let path_to_delete = path_of(&traversal.tree, index);
-            delete_directory_recursively(path_to_delete)?;
-            entries_deleted = self.delete_entries_in_traversal(index, traversal);
+    /// Remove all marked entries, after asking where to move them if needed, and for confirmation unless it
+    /// should be skipped.
+    fn request_deletion(&mut self, mode: MarkMode, pane: &MarkPane, traversal: &Traversal) {
+        let last_path = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|d| d.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        match mode {
+            _ if self.deletion.is_some() => {
+                self.message = Some("Marked entries are already being removed".into());
+            }
+            MarkMode::Move => {
+                let destination = last_path(&self.move_destination);
+                self.prompt = Some(Prompt::new(PromptKind::MoveDestination, destination));
+            }
+            #[cfg(feature = "archive")]
+            MarkMode::Archive { .. } => {
+                let path = last_path(&self.archive_path);
+                self.prompt = Some(Prompt::new(PromptKind::ArchiveDestination, path));
+            }
+            _ => self.confirm_deletion(mode, pane, traversal),
         }
-        Ok(entries_deleted)
     }
 
+    fn confirm_deletion(&mut self, mode: MarkMode, pane: &MarkPane, traversal: &Traversal) {
+        match mode {
+            // Whether the originals are deleted is decided when confirming, so archives are always confirmed.
+            #[cfg(feature = "archive")]
+            MarkMode::Archive { .. } => {
+                self.prompt = Some(Prompt::new(PromptKind::ConfirmRemoval(mode), ""));
+            }
+            _ if self.skip_confirmation => self.start_deletion(mode, pane, traversal),
+            _ => self.prompt = Some(Prompt::new(PromptKind::ConfirmRemoval(mode), "")),
+        }
+    }
+
+    /// Remove all marked entries in the background.
+    fn start_deletion(&mut self, mode: MarkMode, pane: &MarkPane, traversal: &Traversal) {
+        if self.deletion.is_some() {
+            self.message = Some("Marked entries are already being removed".into());
+            return;
+        }
+        // Entries below other marked entries are removed with them.
+        let is_below_marked_entry = |mut index: TreeIndex| {
+            while let Some(parent_idx) = traversal
+                .tree
+                .neighbors_directed(index, Direction::Incoming)
+                .next()
+            {
+                if pane.marked().contains_key(&parent_idx) {
+                    return true;
+                }
+                index = parent_idx;
+            }
+            false
+        };
+        let items = pane
+            .marked_sorted_by_index()
+            .into_iter()
+            .filter(|(index, _)| {
+                traversal.tree.node_weight(**index).is_some() && !is_below_marked_entry(**index)
+            })
+            .map(|(index, mark)| DeletionItem {
+                index: *index,
+                path: mark.path.clone(),
+                size: mark.size,
+            })
+            .collect();
+        let threads = self.walk_options.as_ref().map_or(0, |o| o.threads);
+        let destination = match mode {
+            MarkMode::Move => self.move_destination.clone(),
+            #[cfg(feature = "archive")]
+            MarkMode::Archive { .. } => self.archive_path.clone(),
+            _ => None,
+        };
+        self.deletion = Some(DeletionJob::start(
+            mode,
+            destination,
+            self.removal == RemovalMode::DryRun,
+            items,
+            threads,
+            self.wake_up.clone(),
+        ));
+        self.reset_message();
+    }
+
+    pub fn cancel_deletion(&mut self) {
+        if let Some(job) = &self.deletion {
+            job.cancel();
+        }
+    }
+
+    /// Move all entries trashed during this session back to where they were, and add them to the tree again.
     #[cfg(feature = "trash-move")]
-    pub fn trash_entry(
+    pub fn restore_trashed_entries(&mut self, window: &mut MainWindow, traversal: &mut Traversal) {
+        if self.trashed.is_empty() {
+            self.message = Some("Nothing was moved to the trash bin in this session".into());
+        } else if self.deletion.is_some() {
+            self.message = Some("Entries can be restored once the deletion is done".into());
+        } else {
+            self.restore_from_trash_bin(window, traversal);
+        }
+    }
+
+    #[cfg(all(
+        feature = "trash-move",
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    ))]
+    fn restore_from_trash_bin(&mut self, window: &mut MainWindow, traversal: &mut Traversal) {
+        let walk_options = match &self.walk_options {
+            Some(walk_options) => walk_options.clone(),
+            None => {
+                self.message = Some("Entries can be restored once the scan is done".into());
+                return;
+            }
+        };
+        let items = match trash::os_limited::list() {
+            Ok(items) => items,
+            Err(err) => {
+                self.message = Some(format!("Could not read the trash bin: {err}"));
+                return;
+            }
+        };
+        let root_names = names_of(&traversal.tree, self.root);
+        let selected_names = self.selected.map(|idx| names_of(&traversal.tree, idx));
+
+        // Entries trashed later may have contained entries trashed earlier, so they are restored first.
+        let mut not_restored = Vec::new();
+        let mut num_restored = 0;
+        for entry in std::mem::take(&mut self.trashed).into_iter().rev() {
+            if let Err(err) = crate::interactive::restore_from_trash(&entry, &items) {
+                not_restored.push((entry, err));
+                continue;
+            }
+            num_restored += 1;
+            let (name, parent_names) = entry.names.split_last().expect("at least one name");
+            let parent_idx = node_by_names(&traversal.tree, traversal.root_index, parent_names);
+            if names_of(&traversal.tree, parent_idx) != parent_names {
+                // The parent isn't known anymore, so the entry is found when rescanning it.
+                continue;
+            }
+            if let Ok(Some(subtree)) =
+                Traversal::from_walk(walk_options.clone(), vec![entry.path.clone()], |_| {
+                    Ok(false)
+                })
+            {
+                self.insert_subtree(parent_idx, name.clone(), subtree, traversal);
+            }
+        }
+
+        self.forget_removed_nodes(
+            &HashSet::new(),
+            root_names,
+            selected_names,
+            window,
+            traversal,
+        );
+        self.message = Some(match not_restored.first() {
+            None => format!("Restored {num_restored} entries from the trash bin"),
+            Some((entry, err)) => format!(
+                "Restored {num_restored} entries, {} could not be restored, like '{}': {err}",
+                not_restored.len(),
+                entry.path.display()
+            ),
+        });
+        self.trashed = not_restored
+            .into_iter()
+            .rev()
+            .map(|(entry, _)| entry)
+            .collect();
+    }
+
+    #[cfg(all(
+        feature = "trash-move",
+        not(all(
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        ))
+    ))]
+    fn restore_from_trash_bin(&mut self, _window: &mut MainWindow, _traversal: &mut Traversal) {
+        self.message = Some("Restoring from the trash bin isn't supported on this platform".into());
+    }
+
+    /// Apply everything the background deletion did so far to the tree and the mark pane, without blocking.
+    pub fn poll_deletion(&mut self, window: &mut MainWindow, traversal: &mut Traversal) {
+        while let Some(event) = self.deletion.as_mut().and_then(|job| job.next_event(false)) {
+            self.apply_deletion_event(event, window, traversal);
+        }
+    }
+
+    /// Cancel the background deletion, if there is one, and wait for it to stop.
+    pub fn finish_deletion(&mut self, window: &mut MainWindow, traversal: &mut Traversal) {
+        self.cancel_deletion();
+        while let Some(event) = self.deletion.as_mut().and_then(|job| job.next_event(true)) {
+            self.apply_deletion_event(event, window, traversal);
+        }
+        self.deletion = None;
+    }
+
+    fn apply_deletion_event(
         &mut self,
-        index: TreeIndex,
+        event: DeletionEvent,
+        window: &mut MainWindow,
         traversal: &mut Traversal,
-    ) -> Result<usize, usize> {
-        let mut entries_deleted = 0;
-        if let Some(_entry) = traversal.tree.node_weight(index) {
-            let path_to_delete = path_of(&traversal.tree, index);
-            if trash::delete(path_to_delete).is_err() {
-                return Err(1);
+    ) {
+        match event {
+            DeletionEvent::Started { .. } => {}
+            DeletionEvent::Removed {
+                index,
+                result: Ok(()),
+            } => {
+                if traversal.tree.node_weight(index).is_some() {
+                    match self.deletion.as_mut() {
+                        Some(job) if job.dry_run => self.dry_run_removals.push((
+                            job.mode,
+                            path_of(&traversal.tree, index),
+                            job.destination.clone(),
+                        )),
+                        #[cfg(feature = "trash-move")]
+                        Some(job) if job.mode == MarkMode::Trash => {
+                            let path = path_of(&traversal.tree, index);
+                            match crate::interactive::TrashedEntry::new(
+                                names_of(&traversal.tree, index),
+                                path.clone(),
+                            ) {
+                                Some(entry) => self.trashed.push(entry),
+                                None => {
+                                    job.outcome = Some(format!(
+                                        "'{}' was moved to the trash bin, but can't be restored with Ctrl+z",
+                                        path.display()
+                                    ))
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                    let destination_idx = match self.deletion.as_ref() {
+                        Some(job) if job.mode == MarkMode::Move => {
+                            self.move_destination_in_traversal(traversal)
+                        }
+                        _ => None,
+                    };
+                    let moved = match destination_idx {
+                        Some(destination_idx) => {
+                            self.move_entry_in_traversal(index, destination_idx, traversal)
+                        }
+                        None => false,
+                    };
+                    if moved {
+                        // Marked entries below the moved one moved with it.
+                        let mut moved_entries = HashSet::new();
+                        let mut bfs = Bfs::new(&traversal.tree, index);
+                        while let Some(idx) = bfs.next(&traversal.tree) {
+                            moved_entries.insert(idx);
+                        }
+                        window.mark_pane = window
+                            .mark_pane
+                            .take()
+                            .and_then(|pane| pane.retain(|idx| !moved_entries.contains(&idx)));
+                    } else {
+                        self.delete_entries_in_traversal(index, traversal);
+                    }
+                }
+                // Marked entries below the removed one are gone, too.
+                window.mark_pane = window
+                    .mark_pane
+                    .take()
+                    .and_then(|pane| pane.retain(|idx| traversal.tree.node_weight(idx).is_some()));
+            }
+            DeletionEvent::Removed {
+                index,
+                result: Err(num_errors),
+            } => {
+                if let Some(pane) = window.mark_pane.as_mut() {
+                    pane.set_deletion_errors(index, num_errors);
+                }
+                // It may have failed because the entry is already gone.
+                self.check_existence(index, traversal);
+            }
+            #[cfg(feature = "archive")]
+            DeletionEvent::Archived { result: Ok(()), .. } => {}
+            #[cfg(feature = "archive")]
+            DeletionEvent::Archived {
+                index,
+                result: Err(num_errors),
+            } => {
+                if let Some(pane) = window.mark_pane.as_mut() {
+                    pane.set_deletion_errors(index, num_errors);
+                }
             }
-            entries_deleted = self.delete_entries_in_traversal(index, traversal);
+            #[cfg(feature = "archive")]
+            DeletionEvent::ArchiveWritten { path, result } => {
+                if let Some(job) = self.deletion.as_mut() {
+                    job.outcome = Some(match result {
+                        Ok(_) if job.dry_run => {
+                            format!("Pretended to archive into '{}' (dry run)", path.display())
+                        }
+                        Ok(summary) => format!(
+                            "Archived {} entries into '{}' and verified it",
+                            summary.entries,
+                            path.display()
+                        ),
+                        Err(reason) => format!(
+                            "Nothing was removed - couldn't archive into '{}': {reason}",
+                            path.display()
+                        ),
+                    });
+                }
+            }
+            DeletionEvent::Interrupted { index } => {
+                // Learn what's left of the entry.
+                if let Some(walk_options) = self.walk_options.clone() {
+                    let path = path_of(&traversal.tree, index);
+                    if let Ok(Some(subtree)) =
+                        Traversal::from_walk(walk_options, vec![path], |_| Ok(false))
+                    {
+                        let root_names = names_of(&traversal.tree, self.root);
+                        let selected_names =
+                            self.selected.map(|idx| names_of(&traversal.tree, idx));
+                        let mut removed = HashSet::new();
+                        self.replace_subtree(index, subtree, traversal, &mut removed);
+                        self.forget_removed_nodes(
+                            &removed,
+                            root_names,
+                            selected_names,
+                            window,
+                            traversal,
+                        );
+                    }
+                }
+            }
+            DeletionEvent::Finished => {
+                let outcome = self.deletion.take().and_then(|mut job| job.outcome.take());
+                self.reset_message();
+                if outcome.is_some() {
+                    self.message = outcome;
+                }
+            }
+        }
+        if window.mark_pane.is_none() && matches!(self.focussed, Mark) {
+            self.focussed = Main;
         }
-        Ok(entries_deleted)
     }
 
     pub fn delete_entries_in_traversal(
@@ -305,9 +1036,10 @@ impl AppState {
         while let Some(nx) = bfs.next(&traversal.tree) {
             traversal.tree.remove_node(nx);
             traversal.entries_traversed -= 1;
+            self.expanded.remove(&nx);
             entries_deleted += 1;
         }
-        self.entries = sorted_entries(&traversal.tree, self.root, self.sorting);
+        self.refresh_entries(&traversal.tree);
         if traversal.tree.node_weight(self.root).is_none() {
             self.set_root(traversal.root_index, traversal);
         }
@@ -316,28 +1048,270 @@ impl AppState {
             .and_then(|selected| self.entries.iter().find(|e| e.index == selected))
             .is_none()
         {
-            self.selected = self.entries.get(0).map(|e| e.index);
+            self.selected = self.entries.first().map(|e| e.index);
         }
         self.recompute_sizes_recursively(parent_idx, traversal);
         entries_deleted
     }
 
+    /// Return the directory marked entries are moved into, if it's part of the tree.
+    fn move_destination_in_traversal(&self, traversal: &Traversal) -> Option<TreeIndex> {
+        let destination = std::env::current_dir()
+            .ok()?
+            .join(self.move_destination.as_ref()?);
+        node_by_path(
+            &traversal.tree,
+            traversal.root_index,
+            traversal.root_index,
+            &destination,
+        )
+    }
+
+    /// Move the entry at `index` with everything below it into the directory at `destination_idx`, like it was
+    /// moved on disk, and return `true`. Return `false` without changing the tree if `destination_idx` is below
+    /// the entry itself.
+    fn move_entry_in_traversal(
+        &mut self,
+        index: TreeIndex,
+        destination_idx: TreeIndex,
+        traversal: &mut Traversal,
+    ) -> bool {
+        let tree = &mut traversal.tree;
+        let mut ancestor_idx = Some(destination_idx);
+        while let Some(idx) = ancestor_idx {
+            if idx == index {
+                return false;
+            }
+            ancestor_idx = tree.neighbors_directed(idx, Direction::Incoming).next();
+        }
+        let parent_idx = tree
+            .neighbors_directed(index, Direction::Incoming)
+            .next()
+            .expect("us being unable to move the root index");
+        if let Some(edge) = tree.find_edge(parent_idx, index) {
+            tree.remove_edge(edge);
+        }
+        tree.add_edge(destination_idx, index, ());
+        // Top-level entries are named after the path they were traversed with, but all others by their name.
+        let entry = &mut tree[index];
+        if let Some(name) = entry.name.file_name().map(PathBuf::from) {
+            entry.name = name;
+        }
+
+        self.recompute_sizes_recursively(parent_idx, traversal);
+        self.recompute_sizes_recursively(destination_idx, traversal);
+        self.refresh_entries(&traversal.tree);
+        if self
+            .selected
+            .and_then(|selected| self.entries.iter().find(|e| e.index == selected))
+            .is_none()
+        {
+            self.selected = self.entries.first().map(|e| e.index);
+        }
+        true
+    }
+
+    pub fn rescan_selected_entry<B>(
+        &mut self,
+        window: &mut MainWindow,
+        traversal: &mut Traversal,
+        display: DisplayOptions,
+        terminal: &mut Terminal<B>,
+    ) where
+        B: Backend,
+    {
+        if let Some(index) = self.selected {
+            self.rescan_entries(vec![index], window, traversal, display, terminal);
+        }
+    }
+
+    pub fn rescan_all_entries<B>(
+        &mut self,
+        window: &mut MainWindow,
+        traversal: &mut Traversal,
+        display: DisplayOptions,
+        terminal: &mut Terminal<B>,
+    ) where
+        B: Backend,
+    {
+        let top_level = traversal
+            .tree
+            .neighbors_directed(traversal.root_index, Direction::Outgoing)
+            .collect();
+        self.rescan_entries(top_level, window, traversal, display, terminal);
+    }
+
+    /// Walk the filesystem at each of `indices` again and replace their subtrees with the result,
+    /// keeping the current directory and selection if they still exist.
+    fn rescan_entries<B>(
+        &mut self,
+        indices: Vec<TreeIndex>,
+        window: &mut MainWindow,
+        traversal: &mut Traversal,
+        display: DisplayOptions,
+        terminal: &mut Terminal<B>,
+    ) where
+        B: Backend,
+    {
+        let walk_options = match &self.walk_options {
+            Some(walk_options) => walk_options.clone(),
+            None if self.is_loaded => {
+                self.message = Some("Loaded entries can't be rescanned".into());
+                return;
+            }
+            None => {
+                self.message = Some("Entries can be rescanned once the scan is done".into());
+                return;
+            }
+        };
+        if self.deletion.is_some() {
+            // Results of the deletion refer to nodes that rescanning would replace.
+            self.message = Some("Entries can be rescanned once the deletion is done".into());
+            return;
+        }
+        let root_names = names_of(&traversal.tree, self.root);
+        let selected_names = self.selected.map(|idx| names_of(&traversal.tree, idx));
+
+        let mut removed = HashSet::new();
+        for index in indices {
+            if traversal.tree.node_weight(index).is_none() {
+                continue;
+            }
+            let path = path_of(&traversal.tree, index);
+            self.message = Some(format!("Rescanning '{}'...", path.display()));
+            self.draw(window, traversal, display, terminal).ok();
+            let subtree = Traversal::from_walk(walk_options.clone(), vec![path], |subtree| {
+                self.message = Some(format!(
+                    "Rescanning... {} entries",
+                    subtree.entries_traversed
+                ));
+                self.draw(window, traversal, display, terminal).ok();
+                Ok(false)
+            })
+            .ok()
+            .flatten();
+            if let Some(subtree) = subtree {
+                self.replace_subtree(index, subtree, traversal, &mut removed);
+            }
+        }
+
+        self.forget_removed_nodes(&removed, root_names, selected_names, window, traversal);
+        self.message = None;
+    }
+
+    /// Unmark and unbookmark all `removed` nodes, and restore the current directory and selection by their names.
+    fn forget_removed_nodes(
+        &mut self,
+        removed: &HashSet<TreeIndex>,
+        root_names: Vec<PathBuf>,
+        selected_names: Option<Vec<PathBuf>>,
+        window: &mut MainWindow,
+        traversal: &Traversal,
+    ) {
+        self.bookmarks
+            .retain(|from, to| !removed.contains(from) && !removed.contains(to));
+        self.expanded.retain(|idx| !removed.contains(idx));
+        window.mark_pane = window
+            .mark_pane
+            .take()
+            .and_then(|pane| pane.retain(|index| !removed.contains(&index)));
+        let root = node_by_names(&traversal.tree, traversal.root_index, &root_names);
+        self.set_root(root, traversal);
+        self.selected = selected_names
+            .map(|names| node_by_names(&traversal.tree, traversal.root_index, &names))
+            .and_then(|selected| self.entries.iter().find(|e| e.index == selected))
+            .or_else(|| self.entries.first())
+            .map(|e| e.index);
+    }
+
+    /// Replace the node at `index` and all of its children with the top-level entry of `subtree`,
+    /// and put the indices of all nodes that were removed into `removed`.
+    fn replace_subtree(
+        &mut self,
+        index: TreeIndex,
+        subtree: Traversal,
+        traversal: &mut Traversal,
+        removed: &mut HashSet<TreeIndex>,
+    ) {
+        let parent_idx = traversal
+            .tree
+            .neighbors_directed(index, Direction::Incoming)
+            .next()
+            .expect("us being unable to rescan the root index");
+        let name = traversal.tree[index].name.clone();
+
+        let mut bfs = Bfs::new(&traversal.tree, index);
+        while let Some(nx) = bfs.next(&traversal.tree) {
+            if let Some(entry) = traversal.tree.remove_node(nx) {
+                traversal.io_errors -= u64::from(entry.metadata_io_error).min(traversal.io_errors);
+            }
+            traversal.entries_traversed -= 1;
+            removed.insert(nx);
+        }
+
+        self.insert_subtree(parent_idx, name, subtree, traversal);
+    }
+
+    /// Add the top-level entry of `subtree` with all of its children below `parent_idx`, naming it `name`.
+    /// Nothing is added if `subtree` is empty, as its entry doesn't exist anymore.
+    fn insert_subtree(
+        &mut self,
+        parent_idx: TreeIndex,
+        name: PathBuf,
+        mut subtree: Traversal,
+        traversal: &mut Traversal,
+    ) {
+        let mut stack: Vec<_> = subtree
+            .tree
+            .neighbors_directed(subtree.root_index, Direction::Outgoing)
+            .map(|idx| (idx, parent_idx))
+            .collect();
+        if let Some(&(top_level_idx, _)) = stack.first() {
+            subtree.tree[top_level_idx].name = name;
+        }
+        while let Some((subtree_idx, parent_idx)) = stack.pop() {
+            let new_idx = traversal.tree.add_node(subtree.tree[subtree_idx].clone());
+            traversal.tree.add_edge(parent_idx, new_idx, ());
+            // Children are returned newest first, and are added oldest first to retain their order.
+            stack.extend(
+                subtree
+                    .tree
+                    .neighbors_directed(subtree_idx, Direction::Outgoing)
+                    .map(|idx| (idx, new_idx)),
+            );
+        }
+        traversal.entries_traversed += subtree.entries_traversed;
+        traversal.io_errors += subtree.io_errors;
+        self.recompute_sizes_recursively(parent_idx, traversal);
+    }
+
     fn set_root(&mut self, root: TreeIndex, traversal: &Traversal) {
+        if root != self.root {
+            self.filter = None;
+        }
         self.root = root;
-        self.entries = sorted_entries(&traversal.tree, root, self.sorting);
+        self.refresh_entries(&traversal.tree);
     }
 
     fn recompute_sizes_recursively(&mut self, mut index: TreeIndex, traversal: &mut Traversal) {
         loop {
-            traversal
-                .tree
-                .node_weight_mut(index)
-                .expect("valid index")
-                .size = traversal
+            let (mut size, mut entry_count, mut file_count, mut mtime) = (0, 0, 0, None);
+            for child in traversal
                 .tree
                 .neighbors_directed(index, Direction::Outgoing)
-                .filter_map(|idx| traversal.tree.node_weight(idx).map(|w| w.size))
-                .sum();
+                .filter_map(|idx| traversal.tree.node_weight(idx))
+            {
+                size += child.size;
+                entry_count += child.entry_count + 1;
+                file_count += child.file_count + u64::from(!child.entry_type.is_dir());
+                mtime = mtime.max(child.mtime);
+            }
+            let entry = traversal.tree.node_weight_mut(index).expect("valid index");
+            entry.size = size;
+            entry.entry_count = entry_count;
+            entry.file_count = file_count;
+            // The directory's own modification time is unknown once aggregated, so it's never lowered.
+            entry.mtime = entry.mtime.max(mtime);
             match traversal
                 .tree
                 .neighbors_directed(index, Direction::Incoming)
@@ -383,10 +1357,18 @@ impl AppState {
         cursor: CursorMode,
         mode: MarkEntryMode,
         window: &mut MainWindow,
-        traversal: &Traversal,
+        traversal: &mut Traversal,
     ) {
         if let Some(index) = self.selected {
-            self.mark_entry_by_index(index, mode, window, traversal);
+            let is_marked = window
+                .mark_pane
+                .as_ref()
+                .is_some_and(|pane| pane.marked().contains_key(&index));
+            if is_marked || self.check_existence(index, traversal) {
+                self.mark_entry_by_index(index, mode, window, traversal);
+            } else {
+                self.message = Some("The entry doesn't exist anymore".into());
+            }
         };
         if let CursorMode::Advance = cursor {
             self.change_entry_selection(CursorDirection::Down)
@@ -399,75 +1381,54 @@ impl AppState {
         window: &mut MainWindow,
         traversal: &Traversal,
     ) {
-        for index in self.entries.iter().map(|e| e.index).collect::<Vec<_>>() {
+        // Entries of expanded directories are removed along with their directory anyway.
+        let top_level: Vec<_> = self
+            .entries
+            .iter()
+            .map(|e| e.index)
+            .filter(|idx| {
+                traversal
+                    .tree
+                    .neighbors_directed(*idx, Direction::Incoming)
+                    .next()
+                    == Some(self.root)
+            })
+            .collect();
+        for index in top_level {
             self.mark_entry_by_index(index, mode, window, traversal);
         }
     }
 }
 
-fn into_error_count(res: Result<(), io::Error>) -> usize {
-    match res.map_err(io_err_to_usize) {
-        Ok(_) => 0,
-        Err(c) => c,
+/// Return why marked entries of `pane` can't be archived into a new archive at `path`, if they can't.
+#[cfg(feature = "archive")]
+fn check_archive_path(path: &std::path::Path, pane: &MarkPane) -> Result<(), String> {
+    if dua::ArchiveFormat::from_path(path).is_none() {
+        return Err(format!(
+            "'{}' doesn't end in .tar.zst or .tar.gz",
+            path.display()
+        ));
     }
-}
-
-fn io_err_to_usize(err: io::Error) -> usize {
-    if err.kind() == io::ErrorKind::NotFound {
-        0
-    } else {
-        1
+    if path.symlink_metadata().is_ok() {
+        return Err(format!("'{}' already exists", path.display()));
     }
-}
-
-// TODO: could use jwalk for this
-// see https://github.com/Byron/dua-cli/issues/43
-fn delete_directory_recursively(path: PathBuf) -> Result<(), usize> {
-    let mut files_or_dirs = vec![path];
-    let mut dirs = Vec::new();
-    let mut num_errors = 0;
-    while let Some(path) = files_or_dirs.pop() {
-        let assume_symlink_to_try_deletion = true;
-        let is_symlink = path
-            .symlink_metadata()
-            .map(|m| m.file_type().is_symlink())
-            .unwrap_or(assume_symlink_to_try_deletion);
-        if is_symlink {
-            // do not follow symlinks
-            num_errors += into_error_count(fs::remove_file(&path));
-            continue;
-        }
-        match fs::read_dir(&path) {
-            Ok(iterator) => {
-                dirs.push(path);
-                for entry in iterator {
-                    match entry.map_err(io_err_to_usize) {
-                        Ok(entry) => files_or_dirs.push(entry.path()),
-                        Err(c) => num_errors += c,
-                    }
-                }
-            }
-            // Err(ref e) if e.kind() == io::ErrorKind::NotADirectory => {
-            //     // assume file, save IOps
-            //     num_errors += into_error_count(fs::remove_file(path));
-            //     continue;
-            // }
-            Err(_) => {
-                // TODO: Reintroduce commented code once the `io_error_more` feature is stable
-                // num_errors += 1;
-                num_errors += into_error_count(fs::remove_file(path));
-                continue;
-            }
-        };
-    }
-
-    for dir in dirs.into_iter().rev() {
-        num_errors += into_error_count(fs::remove_dir(&dir).or_else(|_| fs::remove_file(dir)));
-    }
-
-    if num_errors == 0 {
-        Ok(())
-    } else {
-        Err(num_errors)
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let parent = parent
+        .canonicalize()
+        .ok()
+        .filter(|p| p.is_dir())
+        .ok_or_else(|| format!("'{}' is not a directory", parent.display()))?;
+    // The archive would contain itself, and could be deleted along with the originals.
+    if pane
+        .marked()
+        .values()
+        .filter_map(|mark| mark.path.canonicalize().ok())
+        .any(|marked| parent.starts_with(marked))
+    {
+        return Err(format!("'{}' is within a marked entry", path.display()));
     }
+    Ok(())
 }
@@ -1,12 +1,16 @@
 mod bytevis;
 mod common;
+mod deletion;
 mod eventloop;
 mod handlers;
+mod prompt;
 
 pub use bytevis::*;
 pub use common::*;
+pub use deletion::*;
 pub use eventloop::*;
 pub use handlers::*;
+pub use prompt::*;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,87 @@
+use crate::interactive::widgets::MarkMode;
+use crosstermion::input::Key;
+
+/// What the text typed into a [`Prompt`] is used for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PromptKind {
+    /// Narrow the entries of the current directory to the ones matching the input.
+    Search,
+    /// Remove all marked entries in the given way if the input confirms it.
+    ConfirmRemoval(MarkMode),
+    /// The directory to move all marked entries into.
+    MoveDestination,
+    /// The path of the archive to write all marked entries into.
+    #[cfg(feature = "archive")]
+    ArchiveDestination,
+    /// The path of an entry to make the current one, relative to the current directory or absolute.
+    GoTo,
+}
+
+impl PromptKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            PromptKind::Search => "/",
+            PromptKind::ConfirmRemoval(_) => "confirm: ",
+            PromptKind::MoveDestination => "move into: ",
+            #[cfg(feature = "archive")]
+            PromptKind::ArchiveDestination => "archive to: ",
+            PromptKind::GoTo => ":",
+        }
+    }
+}
+
+/// What happened to a [`Prompt`] after processing a key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PromptEvent {
+    /// The input was changed.
+    Changed,
+    /// The input was confirmed, and the prompt should be closed.
+    Submitted,
+    /// The input should be discarded, and the prompt should be closed.
+    Cancelled,
+    /// The input should be completed, if possible.
+    CompletionRequested,
+    /// The key had no effect.
+    Ignored,
+}
+
+/// A single line of text typed by the user, which receives all keys while it's open.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind, input: impl Into<String>) -> Self {
+        Prompt {
+            kind,
+            input: input.into(),
+        }
+    }
+
+    pub fn process_key(&mut self, key: Key) -> PromptEvent {
+        use crosstermion::input::Key::*;
+        match key {
+            Char('\n') => PromptEvent::Submitted,
+            Char('\t') => PromptEvent::CompletionRequested,
+            Esc => PromptEvent::Cancelled,
+            Backspace => {
+                if self.input.pop().is_some() {
+                    PromptEvent::Changed
+                } else {
+                    PromptEvent::Ignored
+                }
+            }
+            Ctrl('u') => {
+                self.input.clear();
+                PromptEvent::Changed
+            }
+            Char(c) if !c.is_control() => {
+                self.input.push(c);
+                PromptEvent::Changed
+            }
+            _ => PromptEvent::Ignored,
+        }
+    }
+}
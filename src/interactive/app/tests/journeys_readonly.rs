@@ -1,4 +1,5 @@
 use anyhow::Result;
+use itertools::Itertools;
 use pretty_assertions::assert_eq;
 use std::ffi::OsString;
 
@@ -6,7 +7,7 @@ use crate::interactive::{
     app::tests::{
         utils::{
             fixture_str, index_by_name, initialized_app_and_terminal_from_fixture, into_keys,
-            node_by_index, node_by_name,
+            node_by_index, node_by_name, rendered,
         },
         FIXTURE_PATH,
     },
@@ -176,9 +177,10 @@ fn simple_user_journey_read_only() -> Result<()> {
                 "it marks only a single node",
             );
             assert!(
-                app.window.mark_pane.as_ref().map_or(false, |p| p
-                    .marked()
-                    .contains_key(&previously_selected_index)),
+                app.window
+                    .mark_pane
+                    .as_ref()
+                    .is_some_and(|p| p.marked().contains_key(&previously_selected_index)),
                 "it marks the selected node"
             );
             assert_eq!(
@@ -216,9 +218,10 @@ fn simple_user_journey_read_only() -> Result<()> {
             );
 
             assert!(
-                app.window.mark_pane.as_ref().map_or(false, |p| p
-                    .marked()
-                    .contains_key(&previously_selected_index)),
+                app.window
+                    .mark_pane
+                    .as_ref()
+                    .is_some_and(|p| p.marked().contains_key(&previously_selected_index)),
                 "it leaves the first selected entry marked"
             );
         }
@@ -274,3 +277,298 @@ fn simple_user_journey_read_only() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn search_journey_read_only() -> Result<()> {
+    use crosstermion::input::{Event, Key};
+
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-01"])?;
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    let num_entries = app.state.entries.len();
+
+    // when typing into the search prompt
+    app.process_events(&mut terminal, into_keys(b"/q*.*".iter()))?;
+    assert!(app.state.prompt.is_some(), "'q' is typed and doesn't quit");
+    assert_eq!(app.state.entries.len(), 0, "nothing matches 'q*.*'");
+
+    app.process_events(
+        &mut terminal,
+        vec![Key::Home, Key::Backspace, Key::Char('q'), Key::Backspace]
+            .into_iter()
+            .map(Event::Key),
+    )?;
+    assert_eq!(
+        app.state.prompt.as_ref().map(|p| p.input.as_str()),
+        Some("q*."),
+        "unknown keys are ignored, and only the last character can be removed"
+    );
+    app.process_events(
+        &mut terminal,
+        vec![Key::Backspace, Key::Backspace, Key::Backspace]
+            .into_iter()
+            .map(Event::Key),
+    )?;
+    app.process_events(&mut terminal, into_keys(b"*.*\n".iter()))?;
+    assert!(app.state.prompt.is_none(), "<Enter> closes the prompt");
+    assert_eq!(
+        app.state
+            .entries
+            .iter()
+            .map(|e| e.data.name.to_string_lossy().into_owned())
+            .sorted()
+            .collect::<Vec<_>>(),
+        [".hidden.666", "b.empty", "c.lnk", "z123.b"],
+        "only entries matching the glob are shown"
+    );
+
+    // n and N cycle through all matches
+    let first_selected = app.state.selected;
+    app.process_events(&mut terminal, into_keys(b"nnnn".iter()))?;
+    assert_eq!(app.state.selected, first_selected, "it wraps around");
+    app.process_events(&mut terminal, into_keys(b"N".iter()))?;
+    assert_eq!(
+        app.state.selected,
+        app.state.entries.last().map(|e| e.index),
+        "and backwards"
+    );
+
+    // <Esc> removes the filter instead of quitting
+    app.process_events(&mut terminal, std::iter::once(Event::Key(Key::Esc)))?;
+    assert!(app.state.filter.is_none());
+    assert_eq!(app.state.entries.len(), num_entries);
+    Ok(())
+}
+
+#[test]
+fn sorting_by_name_count_and_mtime_read_only() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-01"])?;
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    let names = |app: &crate::interactive::TerminalApp| {
+        app.state
+            .entries
+            .iter()
+            .map(|e| e.data.name.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    // when hitting the A key
+    app.process_events(&mut terminal, into_keys(b"A".iter()))?;
+    assert_eq!(app.state.sorting, SortMode::NameAscending);
+    assert_eq!(
+        names(&app),
+        [".hidden.666", "a", "b.empty", "c.lnk", "dir", "z123.b"],
+        "it sorts entries by name"
+    );
+    // when hitting the A key again
+    app.process_events(&mut terminal, into_keys(b"A".iter()))?;
+    assert_eq!(app.state.sorting, SortMode::NameDescending);
+    assert_eq!(names(&app).first().map(String::as_str), Some("z123.b"));
+
+    // when hitting the c key
+    app.process_events(&mut terminal, into_keys(b"c".iter()))?;
+    assert_eq!(app.state.sorting, SortMode::CountDescending);
+    assert_eq!(
+        names(&app).first().map(String::as_str),
+        Some("dir"),
+        "the only directory has the most entries"
+    );
+    assert_eq!(app.state.entries[0].data.entry_count, 7);
+
+    // when hitting the m key
+    app.process_events(&mut terminal, into_keys(b"m".iter()))?;
+    assert_eq!(app.state.sorting, SortMode::MTimeDescending);
+    assert!(
+        app.state
+            .entries
+            .iter()
+            .tuple_windows()
+            .all(|(l, r)| l.data.mtime >= r.data.mtime),
+        "the most recently modified entries come first"
+    );
+    assert!(app.state.entries.iter().all(|e| e.data.mtime.is_some()));
+
+    // when hitting the s key
+    app.process_events(&mut terminal, into_keys(b"s".iter()))?;
+    assert_eq!(
+        app.state.sorting,
+        SortMode::SizeDescending,
+        "switching back starts with the natural direction"
+    );
+    Ok(())
+}
+
+#[test]
+fn tree_view_expands_and_collapses_directories_in_place_read_only() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-01"])?;
+    app.process_events(&mut terminal, into_keys(b"oA".iter()))?;
+    let names = |app: &crate::interactive::TerminalApp| {
+        app.state
+            .entries
+            .iter()
+            .map(|e| e.data.name.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+    let selected_name = |app: &crate::interactive::TerminalApp| {
+        app.state
+            .selected
+            .map(|idx| node_by_index(app, idx).name.to_string_lossy().into_owned())
+    };
+    let top_level = names(&app);
+    terminal.backend.resize(100, 20);
+
+    // when switching to the tree view and expanding the directory
+    app.process_events(&mut terminal, into_keys(b"tGkl".iter()))?;
+    assert_eq!(
+        names(&app),
+        [
+            ".hidden.666",
+            "a",
+            "b.empty",
+            "c.lnk",
+            "dir",
+            "1000bytes",
+            "dir-a.1mb",
+            "dir-a.kb",
+            "empty-dir",
+            "sub",
+            "z123.b"
+        ],
+        "its entries are shown right below it, sorted the same way"
+    );
+    assert_eq!(selected_name(&app).as_deref(), Some("dir"));
+    let rendered = rendered(&terminal);
+    assert!(
+        rendered.contains("▾/dir"),
+        "expanded directories are marked"
+    );
+    assert!(rendered.contains("  ▸/sub"), "entries are indented");
+
+    // when expanding an expanded directory, and expanding the directory within it
+    app.process_events(&mut terminal, into_keys(b"l".iter()))?;
+    assert_eq!(selected_name(&app).as_deref(), Some("1000bytes"));
+    app.process_events(&mut terminal, into_keys(b"jjjjl".iter()))?;
+    assert_eq!(names(&app)[10], "dir-sub-a.256kb");
+
+    // when collapsing from an entry within a directory
+    app.process_events(&mut terminal, into_keys(b"jhh".iter()))?;
+    assert_eq!(selected_name(&app).as_deref(), Some("sub"));
+    assert_eq!(names(&app).len(), 11, "the collapsed entries are hidden");
+
+    // when switching back to the flat view
+    app.process_events(&mut terminal, into_keys(b"t".iter()))?;
+    assert_eq!(names(&app), top_level);
+    assert_eq!(
+        selected_name(&app).as_deref(),
+        Some("dir"),
+        "the directory containing the selection is selected"
+    );
+    Ok(())
+}
+
+#[test]
+fn breadcrumbs_show_ancestors_and_jump_to_them_read_only() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-01"])?;
+    terminal.backend.resize(100, 20);
+    let header = |terminal: &tui_react::Terminal<tui::backend::TestBackend>| -> String {
+        let buffer = terminal.backend.buffer();
+        buffer.content[..buffer.area.width as usize]
+            .iter()
+            .map(|cell| cell.symbol.as_str())
+            .collect()
+    };
+    let selected_name = |app: &crate::interactive::TerminalApp| {
+        app.state
+            .selected
+            .map(|idx| node_by_index(app, idx).name.to_string_lossy().into_owned())
+    };
+
+    // when descending three levels
+    app.process_events(&mut terminal, into_keys(b"ooj".iter()))?;
+    assert_eq!(selected_name(&app).as_deref(), Some("sub"));
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    let breadcrumbs = header(&terminal);
+    assert!(
+        breadcrumbs.contains("[1]dir 1.26 MB › sub 256.00 KB"),
+        "ancestors are numbered by how far up they are: {}",
+        breadcrumbs
+    );
+    assert!(breadcrumbs.starts_with(" [3]. "));
+
+    // when jumping up two levels
+    app.process_events(&mut terminal, into_keys(b"2".iter()))?;
+    assert_eq!(
+        node_by_index(&app, app.state.root).name,
+        node_by_name(&app, fixture_str("sample-01")).name
+    );
+    assert_eq!(
+        selected_name(&app).as_deref(),
+        Some("dir"),
+        "the directory leading back down is selected"
+    );
+
+    // when jumping up further than possible
+    app.process_events(&mut terminal, into_keys(b"9".iter()))?;
+    assert_eq!(app.state.root, app.traversal.root_index);
+    app.process_events(&mut terminal, into_keys(b"1".iter()))?;
+    assert_eq!(app.state.message.as_deref(), Some("Top level reached"));
+    Ok(())
+}
+
+#[test]
+fn go_to_prompt_completes_paths_and_navigates_there_read_only() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-01"])?;
+    let root_name = |app: &crate::interactive::TerminalApp| {
+        node_by_index(app, app.state.root)
+            .name
+            .to_string_lossy()
+            .into_owned()
+    };
+    let selected_name = |app: &crate::interactive::TerminalApp| {
+        app.state
+            .selected
+            .map(|idx| node_by_index(app, idx).name.to_string_lossy().into_owned())
+    };
+
+    // when completing a partial path
+    app.process_events(&mut terminal, into_keys(b"o:dir/su\t".iter()))?;
+    assert_eq!(
+        app.state.prompt.as_ref().map(|p| p.input.as_str()),
+        Some("dir/sub/"),
+        "directories with entries are completed with a trailing slash"
+    );
+
+    // when submitting it
+    app.process_events(&mut terminal, into_keys(b"\n".iter()))?;
+    assert!(app.state.prompt.is_none());
+    assert_eq!(root_name(&app), "sub");
+    assert_eq!(selected_name(&app).as_deref(), Some("dir-sub-a.256kb"));
+
+    // when going up again, the way down is remembered
+    app.process_events(&mut terminal, into_keys(b"h".iter()))?;
+    assert_eq!(root_name(&app), "dir");
+    assert_eq!(selected_name(&app).as_deref(), Some("sub"));
+
+    // when going to a file relative to the current directory
+    app.process_events(&mut terminal, into_keys(b":../z1\t\n".iter()))?;
+    assert_eq!(root_name(&app), fixture_str("sample-01"));
+    assert_eq!(
+        selected_name(&app).as_deref(),
+        Some("z123.b"),
+        "files are selected in their directory"
+    );
+
+    // when there are several candidates
+    app.process_events(&mut terminal, into_keys(b":dir/dir-a\t".iter()))?;
+    assert_eq!(
+        app.state.prompt.as_ref().map(|p| p.input.as_str()),
+        Some("dir/dir-a."),
+        "the common beginning is completed"
+    );
+    assert_eq!(app.state.message.as_deref(), Some("dir-a.1mb  dir-a.kb"));
+
+    // when the path doesn't exist
+    app.process_events(&mut terminal, into_keys(b"\n:nope\n".iter()))?;
+    assert_eq!(app.state.message.as_deref(), Some("No entry at 'nope'"));
+    assert_eq!(root_name(&app), fixture_str("sample-01"));
+    Ok(())
+}
@@ -1,16 +1,21 @@
 use crate::interactive::app::tests::utils::{
-    initialized_app_and_terminal_from_paths, into_keys, WritableFixture,
+    initialized_app_and_terminal_from_paths, into_keys, new_test_terminal, node_by_index,
+    wait_for_deletion, walk_options, WritableFixture,
 };
+use crate::interactive::{widgets::MarkMode, Interaction, RemovalMode, TerminalApp};
+use crate::testing::TempDir;
 use anyhow::Result;
 use crosstermion::input::Event;
 use crosstermion::input::Key;
+use dua::traverse::{EntryType, Traversal};
 use pretty_assertions::assert_eq;
 
 #[test]
 #[cfg(not(target_os = "windows"))] // it stopped working here, don't know if it's truly broken or if it's the test. Let's wait for windows users to report.
 fn basic_user_journey_with_deletion() -> Result<()> {
     let fixture = WritableFixture::from("sample-02");
-    let (mut terminal, mut app) = initialized_app_and_terminal_from_paths(&[fixture.root.clone()])?;
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
 
     // With a selection of items
     app.process_events(&mut terminal, into_keys(b"doddd".iter()))?;
@@ -28,6 +33,25 @@ fn basic_user_journey_with_deletion() -> Result<()> {
         &mut terminal,
         vec![Event::Key(Key::Char('\t')), Event::Key(Key::Ctrl('r'))].into_iter(),
     )?;
+    assert!(
+        app.state.prompt.is_some() && app.state.deletion.is_none(),
+        "nothing is removed before it's confirmed"
+    );
+
+    // Typing something other than 'y' or the amount of marked entries removes nothing
+    app.process_events(&mut terminal, into_keys(b"3\n".iter()))?;
+    assert!(app.state.prompt.is_none());
+    assert!(app.state.deletion.is_none());
+    assert!(fixture.as_ref().is_dir(), "the directory is still there");
+
+    // Confirming with the amount of marked entries removes them
+    app.process_events(&mut terminal, vec![Event::Key(Key::Ctrl('r'))].into_iter())?;
+    app.process_events(&mut terminal, into_keys(b"4\n".iter()))?;
+    assert!(
+        app.state.deletion.is_some(),
+        "entries are removed in the background"
+    );
+    wait_for_deletion(&mut app, &mut terminal)?;
     assert!(
         app.window.mark_pane.is_none(),
         "the marker pane is gone as all entries have been removed"
@@ -43,3 +67,640 @@ fn basic_user_journey_with_deletion() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn rescanning_updates_sizes_and_keeps_the_selection() -> Result<()> {
+    let fixture = WritableFixture::from("sample-01");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    let (total_bytes, entries_traversed) =
+        (app.traversal.total_bytes, app.traversal.entries_traversed);
+
+    // Entering the top-level directory selects the largest entry
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    let selected_size = |app: &TerminalApp| {
+        let entry = node_by_index(app, app.state.selected.expect("a selection"));
+        (entry.name.clone(), entry.size)
+    };
+    let (name, size) = selected_size(&app);
+    assert_eq!(name.to_str(), Some("dir"));
+    let num_entries = app.state.entries.len();
+
+    std::fs::write(fixture.root.join("dir").join("new-file"), [0; 500])?;
+    app.process_events(&mut terminal, into_keys(b"r".iter()))?;
+    assert_eq!(
+        selected_size(&app),
+        (name.clone(), size + 500),
+        "the rescanned entry is selected again, with the new file"
+    );
+    assert_eq!(app.traversal.total_bytes, total_bytes.map(|b| b + 500));
+    assert_eq!(app.traversal.entries_traversed, entries_traversed + 1);
+    assert_eq!(
+        app.state.entries.len(),
+        num_entries,
+        "the directory still lists the same entries"
+    );
+
+    std::fs::remove_file(fixture.root.join("dir").join("new-file"))?;
+    app.process_events(&mut terminal, into_keys(b"R".iter()))?;
+    assert_eq!(
+        selected_size(&app),
+        (name, size),
+        "after rescanning everything, the current directory and selection are restored"
+    );
+    assert_eq!(app.traversal.total_bytes, total_bytes);
+    assert_eq!(app.traversal.entries_traversed, entries_traversed);
+
+    std::fs::remove_file(fixture.root.join("a"))?;
+    app.refresh_view(&mut terminal);
+    let entry_named_a = |app: &TerminalApp| {
+        app.state
+            .entries
+            .iter()
+            .find(|e| e.data.name.to_str() == Some("a"))
+            .map(|e| e.exists)
+    };
+    assert_eq!(
+        entry_named_a(&app),
+        Some(true),
+        "existence isn't checked when showing entries"
+    );
+    app.process_events(&mut terminal, into_keys(b"R".iter()))?;
+    assert_eq!(
+        entry_named_a(&app),
+        None,
+        "rescanning removes entries that don't exist anymore"
+    );
+    Ok(())
+}
+
+#[test]
+fn cancelled_deletion_keeps_the_tree_in_sync_with_the_disk() -> Result<()> {
+    let fixture = WritableFixture::from("emoji");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+
+    // Mark all entries of the top-level directory, then delete and cancel right away.
+    app.process_events(&mut terminal, into_keys(b"oa".iter()))?;
+    let marked: Vec<_> = app
+        .window
+        .mark_pane
+        .as_ref()
+        .expect("marked entries")
+        .marked()
+        .values()
+        .map(|mark| mark.path.clone())
+        .collect();
+    app.process_events(
+        &mut terminal,
+        vec![
+            Event::Key(Key::Char('\t')),
+            Event::Key(Key::Ctrl('r')),
+            Event::Key(Key::Char('y')),
+            Event::Key(Key::Char('\n')),
+            Event::Key(Key::Ctrl('x')),
+        ]
+        .into_iter(),
+    )?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+
+    let marked_in_pane = app
+        .window
+        .mark_pane
+        .as_ref()
+        .map(|pane| pane.marked().len())
+        .unwrap_or(0);
+    let remaining_on_disk = marked
+        .iter()
+        .filter(|path| path.symlink_metadata().is_ok())
+        .count();
+    assert_eq!(
+        marked_in_pane, remaining_on_disk,
+        "entries that weren't removed are still marked"
+    );
+    assert_eq!(
+        app.state.entries.len(),
+        remaining_on_disk,
+        "only entries that weren't removed are left in the tree"
+    );
+    Ok(())
+}
+
+#[test]
+fn entries_removed_behind_the_apps_back_are_found_missing_when_marked() -> Result<()> {
+    let fixture = WritableFixture::from("sample-02");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    let index = app
+        .state
+        .entries
+        .iter()
+        .find(|e| e.data.name.as_os_str() == "a")
+        .map(|e| e.index)
+        .expect("file 'a'");
+    app.state.selected = Some(index);
+
+    std::fs::remove_file(fixture.root.join("a"))?;
+    assert!(
+        app.state.entries.iter().all(|e| e.exists),
+        "existence isn't checked without a reason"
+    );
+
+    app.process_events(&mut terminal, into_keys(b" ".iter()))?;
+    assert!(
+        app.window.mark_pane.is_none(),
+        "missing entries aren't marked"
+    );
+    assert_eq!(
+        app.state.message.as_deref(),
+        Some("The entry doesn't exist anymore")
+    );
+    assert_eq!(node_by_index(&app, index).entry_type, EntryType::Missing);
+    assert!(
+        !app.state
+            .entries
+            .iter()
+            .find(|e| e.index == index)
+            .expect("still listed")
+            .exists
+    );
+    Ok(())
+}
+
+#[test]
+fn loaded_snapshots_leave_the_disk_untouched() -> Result<()> {
+    let fixture = WritableFixture::from("grapehemes");
+    let (_, app) = initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    let mut snapshot = Vec::new();
+    app.traversal.save_snapshot(&mut snapshot)?;
+    let mut terminal = new_test_terminal()?;
+    let (_, mut app) = TerminalApp::initialize_with_traversal(
+        &mut terminal,
+        walk_options(),
+        Traversal::from_snapshot(snapshot.as_slice())?,
+        Interaction::None,
+    )?;
+    assert_eq!(app.state.removal, RemovalMode::ReadOnly);
+
+    app.process_events(&mut terminal, into_keys(b"oa".iter()))?;
+    let num_entries = app.state.entries.len();
+    app.process_events(
+        &mut terminal,
+        vec![
+            Event::Key(Key::Char('\t')),
+            Event::Key(Key::Ctrl('r')),
+            Event::Key(Key::Char('y')),
+            Event::Key(Key::Char('\n')),
+            Event::Key(Key::Ctrl('o')),
+            Event::Key(Key::Char('\t')),
+        ]
+        .into_iter(),
+    )?;
+    assert!(app.state.deletion.is_none(), "nothing is removed");
+    assert!(app.state.prompt.is_none(), "no destination is asked for");
+    assert_eq!(
+        app.window.mark_pane.as_ref().map(|p| p.marked().len()),
+        Some(num_entries)
+    );
+
+    for key in [b"r", b"R"] {
+        app.process_events(&mut terminal, into_keys(key.iter()))?;
+        assert_eq!(
+            app.state.message.as_deref(),
+            Some("Loaded entries can't be rescanned")
+        );
+    }
+    assert_eq!(app.state.entries.len(), num_entries);
+    assert_eq!(
+        std::fs::read_dir(&fixture.root)?.count(),
+        num_entries,
+        "all files still exist"
+    );
+    Ok(())
+}
+
+#[test]
+fn imported_ncdu_exports_leave_the_disk_untouched() -> Result<()> {
+    let fixture = WritableFixture::from("graphemes-difficult");
+    let (_, app) = initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    let mut export = Vec::new();
+    app.traversal.export_ncdu(&mut export, true)?;
+    let mut terminal = new_test_terminal()?;
+    let (_, mut app) = TerminalApp::initialize_with_traversal(
+        &mut terminal,
+        walk_options(),
+        Traversal::from_ncdu_export(export.as_slice(), &walk_options())?,
+        Interaction::None,
+    )?;
+    assert_eq!(app.state.removal, RemovalMode::ReadOnly);
+
+    // The top-level entry is the absolute path of the fixture.
+    app.process_events(
+        &mut terminal,
+        vec![
+            Event::Key(Key::Char('d')),
+            Event::Key(Key::Char('\t')),
+            Event::Key(Key::Ctrl('r')),
+            Event::Key(Key::Char('y')),
+            Event::Key(Key::Char('\n')),
+        ]
+        .into_iter(),
+    )?;
+    assert!(app.state.deletion.is_none(), "nothing is removed");
+    assert!(fixture.root.is_dir());
+    assert_eq!(std::fs::read_dir(&fixture.root)?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn read_only_and_dry_run_leave_the_disk_untouched() -> Result<()> {
+    let fixture = WritableFixture::from("right-to-left");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.process_events(&mut terminal, into_keys(b"oa".iter()))?;
+    let num_entries = app.state.entries.len();
+    let delete_marked = || {
+        vec![
+            Event::Key(Key::Char('\t')),
+            Event::Key(Key::Ctrl('r')),
+            Event::Key(Key::Char('y')),
+            Event::Key(Key::Char('\n')),
+            Event::Key(Key::Char('\t')),
+        ]
+        .into_iter()
+    };
+
+    app.state.removal = RemovalMode::ReadOnly;
+    app.process_events(&mut terminal, delete_marked())?;
+    assert!(
+        app.state.deletion.is_none(),
+        "marked entries can't be removed"
+    );
+    assert_eq!(
+        app.window.mark_pane.as_ref().map(|p| p.marked().len()),
+        Some(num_entries)
+    );
+
+    app.state.removal = RemovalMode::DryRun;
+    app.process_events(&mut terminal, delete_marked())?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert!(
+        app.window.mark_pane.is_none(),
+        "all marked entries are removed from the tree"
+    );
+    assert!(app.state.entries.is_empty());
+    assert_eq!(app.state.dry_run_removals.len(), num_entries);
+    assert!(
+        app.state
+            .dry_run_removals
+            .iter()
+            .all(|(_, path, destination)| path.exists() && destination.is_none()),
+        "nothing was removed from disk"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(all(
+    feature = "trash-move",
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn trashed_entries_can_be_restored() -> Result<()> {
+    // Set in the process running the actual test to the directory of its trash bin.
+    const DATA_HOME: &str = "DUA_TEST_DATA_HOME";
+    if let Some(data_home) = std::env::var_os(DATA_HOME) {
+        return restore_trashed_entries(std::path::Path::new(&data_home));
+    }
+    // Use a trash bin of our own instead of the one of the user. It's found through the environment, which
+    // is shared with all tests running in parallel, so it's only changed in a process of its own.
+    let data_home = TempDir::new("dua-unit-data-home")?;
+    let test_name = concat!(module_path!(), "::trashed_entries_can_be_restored");
+    let status = std::process::Command::new(std::env::current_exe()?)
+        .args([
+            test_name
+                .split_once("::")
+                .map_or(test_name, |(_, name)| name),
+            "--exact",
+            "--nocapture",
+        ])
+        .env("XDG_DATA_HOME", data_home.path())
+        .env(DATA_HOME, data_home.path())
+        .status()?;
+    assert!(status.success(), "the test passes in its own process");
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "trash-move",
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn restore_trashed_entries(data_home: &std::path::Path) -> Result<()> {
+    let fixture = WritableFixture::from("unicode-numbers");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.process_events(&mut terminal, into_keys(b"o ".iter()))?;
+    let num_entries = app.state.entries.len();
+    let total_bytes = app.traversal.total_bytes;
+    let trashed = app
+        .window
+        .mark_pane
+        .as_ref()
+        .and_then(|pane| pane.marked().values().next().map(|mark| mark.path.clone()))
+        .expect("a marked entry");
+
+    app.process_events(
+        &mut terminal,
+        vec![
+            Event::Key(Key::Char('\t')),
+            Event::Key(Key::Ctrl('t')),
+            Event::Key(Key::Char('y')),
+            Event::Key(Key::Char('\n')),
+        ]
+        .into_iter(),
+    )?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert!(!trashed.exists(), "the entry was moved to the trash bin");
+    assert_eq!(app.state.entries.len(), num_entries - 1);
+
+    app.process_events(&mut terminal, vec![Event::Key(Key::Ctrl('z'))].into_iter())?;
+    assert!(trashed.exists(), "the entry was restored");
+    assert_eq!(
+        app.state.entries.len(),
+        num_entries,
+        "the restored entry is back in the tree"
+    );
+    assert_eq!(app.traversal.total_bytes, total_bytes);
+    assert!(app.state.trashed.is_empty());
+
+    app.process_events(&mut terminal, vec![Event::Key(Key::Ctrl('z'))].into_iter())?;
+    assert_eq!(
+        app.state.message.as_deref(),
+        Some("Nothing was moved to the trash bin in this session")
+    );
+    assert!(
+        data_home.join("Trash/files").read_dir()?.next().is_none(),
+        "the trash bin of the test was used, and is empty again"
+    );
+    Ok(())
+}
+
+#[test]
+fn marked_entries_can_be_moved_into_another_directory() -> Result<()> {
+    let fixture = WritableFixture::from("upside-down");
+    let tmp = TempDir::new("dua-unit-move-destination")?;
+    let destination = tmp.path();
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.process_events(&mut terminal, into_keys(b"o ".iter()))?;
+    let moved = app
+        .window
+        .mark_pane
+        .as_ref()
+        .and_then(|pane| pane.marked().values().next().map(|mark| mark.path.clone()))
+        .expect("a marked entry");
+
+    let type_text = |text: &str| {
+        text.chars()
+            .map(|c| Event::Key(Key::Char(c)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    };
+    app.process_events(
+        &mut terminal,
+        vec![Event::Key(Key::Char('\t')), Event::Key(Key::Ctrl('o'))].into_iter(),
+    )?;
+    app.process_events(&mut terminal, type_text("does-not-exist\n"))?;
+    assert!(
+        app.state.prompt.is_none() && app.state.deletion.is_none(),
+        "nothing is moved into directories that don't exist"
+    );
+
+    app.process_events(&mut terminal, vec![Event::Key(Key::Ctrl('o'))].into_iter())?;
+    app.process_events(
+        &mut terminal,
+        type_text(&format!("{}\ny\n", destination.display())),
+    )?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert!(!moved.exists(), "the entry is gone from where it was");
+    assert!(destination
+        .join(moved.file_name().expect("a name"))
+        .is_file());
+    assert!(
+        app.state.entries.is_empty(),
+        "the moved entry is removed from the tree"
+    );
+    assert_eq!(app.traversal.total_bytes, Some(0));
+    Ok(())
+}
+
+#[test]
+fn dry_runs_remember_where_entries_would_have_been_moved() -> Result<()> {
+    let fixture = WritableFixture::from("upside-down");
+    let tmp = TempDir::new("dua-unit-dry-run-move-destination")?;
+    let destination = tmp.path();
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.state.removal = RemovalMode::DryRun;
+    app.process_events(&mut terminal, into_keys(b"o ".iter()))?;
+    let moved = app
+        .window
+        .mark_pane
+        .as_ref()
+        .and_then(|pane| pane.marked().values().next().map(|mark| mark.path.clone()))
+        .expect("a marked entry");
+
+    app.process_events(
+        &mut terminal,
+        vec![Event::Key(Key::Char('\t')), Event::Key(Key::Ctrl('o'))]
+            .into_iter()
+            .chain(into_keys(
+                format!("{}\ny\n", destination.display()).as_bytes().iter(),
+            )),
+    )?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert_eq!(
+        app.state.dry_run_removals,
+        vec![(MarkMode::Move, moved.clone(), Some(destination.to_owned()))]
+    );
+    assert!(moved.exists(), "nothing was moved on disk");
+    Ok(())
+}
+
+#[test]
+fn entries_moved_into_a_directory_of_the_tree_are_moved_within_it() -> Result<()> {
+    let fixture = WritableFixture::from("sample-02");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    let index_of = |app: &TerminalApp, name: &str| {
+        app.state
+            .entries
+            .iter()
+            .find(|e| e.data.name.as_os_str() == name)
+            .map(|e| e.index)
+            .expect("a listed entry")
+    };
+    let (moved, dir) = (index_of(&app, "b"), index_of(&app, "dir"));
+    let (moved_size, dir_size) = (
+        node_by_index(&app, moved).size,
+        node_by_index(&app, dir).size,
+    );
+    let total_bytes = app.traversal.total_bytes;
+    app.state.selected = Some(moved);
+    app.process_events(&mut terminal, into_keys(b" ".iter()))?;
+
+    app.process_events(
+        &mut terminal,
+        vec![Event::Key(Key::Char('\t')), Event::Key(Key::Ctrl('o'))].into_iter(),
+    )?;
+    app.process_events(
+        &mut terminal,
+        format!("{}\ny\n", fixture.root.join("dir").display())
+            .chars()
+            .map(|c| Event::Key(Key::Char(c)))
+            .collect::<Vec<_>>()
+            .into_iter(),
+    )?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert!(fixture.root.join("dir/b").is_file());
+    assert!(app.window.mark_pane.is_none());
+    assert!(
+        app.state.entries.iter().all(|e| e.index != moved),
+        "the moved entry isn't listed where it was"
+    );
+    assert_eq!(
+        app.traversal
+            .tree
+            .neighbors_directed(moved, petgraph::Incoming)
+            .next(),
+        Some(dir),
+        "the moved entry is in the tree where it was moved to"
+    );
+    assert_eq!(node_by_index(&app, dir).size, dir_size + moved_size);
+    assert_eq!(app.traversal.total_bytes, total_bytes);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "archive")]
+fn marked_entries_can_be_archived_and_deleted_afterwards() -> Result<()> {
+    let fixture = WritableFixture::from("regional-indicators");
+    let tmp = TempDir::new("dua-unit-archive")?;
+    let archive = tmp.path().join("archive.tar.zst");
+    let second_archive = tmp.path().join("archive.tar.gz");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.process_events(&mut terminal, into_keys(b"oa".iter()))?;
+    let archived: Vec<_> = app
+        .window
+        .mark_pane
+        .as_ref()
+        .map(|pane| pane.marked().values().map(|m| m.path.clone()).collect())
+        .unwrap_or_default();
+    assert_eq!(archived.len(), 2, "all entries are marked");
+
+    let type_text = |text: &str| {
+        text.chars()
+            .map(|c| Event::Key(Key::Char(c)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    };
+    app.process_events(
+        &mut terminal,
+        vec![Event::Key(Key::Char('\t')), Event::Key(Key::Ctrl('a'))].into_iter(),
+    )?;
+    app.process_events(
+        &mut terminal,
+        type_text(&format!("{}\n", fixture.root.join("a.zip").display())),
+    )?;
+    assert!(
+        app.state.prompt.is_none() && app.state.deletion.is_none(),
+        "archives are only written in supported formats"
+    );
+
+    app.process_events(&mut terminal, vec![Event::Key(Key::Ctrl('a'))].into_iter())?;
+    app.process_events(
+        &mut terminal,
+        type_text(&format!("{}\ny\n", archive.display())),
+    )?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert!(archive.is_file(), "the archive was written");
+    assert!(
+        archived.iter().all(|path| path.exists()),
+        "the originals are kept"
+    );
+    assert_eq!(app.state.entries.len(), 2);
+    assert!(app
+        .state
+        .message
+        .as_deref()
+        .is_some_and(|m| m.starts_with("Archived 2 entries")));
+
+    app.process_events(
+        &mut terminal,
+        vec![Event::Key(Key::Ctrl('a')), Event::Key(Key::Ctrl('u'))].into_iter(),
+    )?;
+    app.process_events(
+        &mut terminal,
+        type_text(&format!("{}\ndelete\n", second_archive.display())),
+    )?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert!(second_archive.is_file(), "the archive was written");
+    assert!(
+        archived.iter().all(|path| !path.exists()),
+        "the originals are deleted once the archive was verified"
+    );
+    assert!(
+        app.state.entries.is_empty(),
+        "the deleted entries are removed from the tree"
+    );
+    assert_eq!(app.traversal.total_bytes, Some(0));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "archive")]
+fn archiving_without_confirmation_still_asks_whether_to_delete_the_originals() -> Result<()> {
+    let fixture = WritableFixture::from("regional-indicators");
+    let tmp = TempDir::new("dua-unit-archive-no-confirm")?;
+    let archive = tmp.path().join("archive.tar.zst");
+    let (mut terminal, mut app) =
+        initialized_app_and_terminal_from_paths(std::slice::from_ref(&fixture.root))?;
+    app.state.skip_confirmation = true;
+    app.process_events(&mut terminal, into_keys(b"oa".iter()))?;
+    let archived: Vec<_> = app
+        .window
+        .mark_pane
+        .as_ref()
+        .map(|pane| pane.marked().values().map(|m| m.path.clone()).collect())
+        .unwrap_or_default();
+
+    app.process_events(
+        &mut terminal,
+        vec![Event::Key(Key::Char('\t')), Event::Key(Key::Ctrl('a'))]
+            .into_iter()
+            .chain(into_keys(
+                format!("{}\n", archive.display()).as_bytes().iter(),
+            )),
+    )?;
+    assert!(
+        app.state.deletion.is_none() && app.state.prompt.is_some(),
+        "nothing is archived before it's known what happens to the originals"
+    );
+
+    app.process_events(&mut terminal, into_keys(b"delete\n".iter()))?;
+    wait_for_deletion(&mut app, &mut terminal)?;
+    assert!(archive.is_file(), "the archive was written");
+    assert!(
+        archived.iter().all(|path| !path.exists()),
+        "the originals are deleted once the archive was verified"
+    );
+    Ok(())
+}
@@ -1,7 +1,11 @@
 use crate::interactive::app::tests::utils::{
-    debug, initialized_app_and_terminal_from_fixture, sample_01_tree, sample_02_tree,
+    debug, initialized_app_and_terminal_from_fixture, into_keys, new_test_terminal, rendered,
+    sample_01_tree, sample_02_tree, walk_options, without_mtimes,
 };
+use crate::interactive::{FocussedPane, Interaction, RemovalMode, TerminalApp};
 use anyhow::Result;
+use crosstermion::input::{Event, Key};
+use dua::traverse::Traversal;
 use pretty_assertions::assert_eq;
 
 #[test]
@@ -10,7 +14,7 @@ fn it_can_handle_ending_traversal_reaching_top_but_skipping_levels() -> Result<(
     let expected_tree = sample_01_tree();
 
     assert_eq!(
-        debug(app.traversal.tree),
+        debug(without_mtimes(&app.traversal.tree)),
         debug(expected_tree),
         "filesystem graph is stable and matches the directory structure"
     );
@@ -23,9 +27,240 @@ fn it_can_handle_ending_traversal_without_reaching_the_top() -> Result<()> {
     let expected_tree = sample_02_tree();
 
     assert_eq!(
-        debug(app.traversal.tree),
+        debug(without_mtimes(&app.traversal.tree)),
         debug(expected_tree),
         "filesystem graph is stable and matches the directory structure"
     );
     Ok(())
 }
+
+#[test]
+fn it_can_be_initialized_from_a_snapshot_without_traversing_the_filesystem() -> Result<()> {
+    let (_, app) = initialized_app_and_terminal_from_fixture(&["sample-02"])?;
+    let mut snapshot = Vec::new();
+    app.traversal.save_snapshot(&mut snapshot)?;
+
+    let mut terminal = new_test_terminal()?;
+    let (_, loaded_app) = TerminalApp::initialize_with_traversal(
+        &mut terminal,
+        walk_options(),
+        Traversal::from_snapshot(snapshot.as_slice())?,
+        Interaction::None,
+    )?;
+
+    assert_eq!(
+        debug(without_mtimes(&loaded_app.traversal.tree)),
+        debug(sample_02_tree()),
+        "the snapshot contains the entire graph"
+    );
+    assert_eq!(loaded_app.state.entries.len(), 1, "the sample-02 root");
+    assert_eq!(loaded_app.state.selected, app.state.selected);
+    Ok(())
+}
+
+#[test]
+fn it_shows_size_changes_compared_to_a_baseline() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-02"])?;
+    let mut snapshot = Vec::new();
+    app.traversal.save_snapshot(&mut snapshot)?;
+    let mut baseline = Traversal::from_snapshot(snapshot.as_slice())?;
+    let top_level_idx = baseline
+        .tree
+        .neighbors_directed(baseline.root_index, petgraph::Outgoing)
+        .next()
+        .expect("sample-02");
+    baseline.tree[top_level_idx].size -= 40;
+
+    app.state.baseline = Some(baseline);
+    app.refresh_view(&mut terminal);
+
+    let rendered = rendered(&terminal);
+    assert!(
+        rendered.contains("+     40  B"),
+        "the entry grew by 40 bytes since the baseline"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_shows_modification_time_and_file_count_columns_when_toggled() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-02"])?;
+    let top_level_idx = app.state.entries[0].index;
+    let three_years = std::time::Duration::from_secs(3 * 365 * 24 * 3600 + 60);
+    app.traversal.tree[top_level_idx].mtime = Some(std::time::SystemTime::now() - three_years);
+    assert_eq!(
+        app.traversal.tree[top_level_idx].file_count, 6,
+        "directories aren't counted"
+    );
+    app.state.refresh_entries(&app.traversal.tree);
+    terminal.backend.resize(100, 20);
+
+    app.refresh_view(&mut terminal);
+    assert!(
+        !rendered(&terminal).contains("3 years ago"),
+        "hidden by default"
+    );
+
+    app.process_events(&mut terminal, into_keys(b"MC".iter()))?;
+    assert!(rendered(&terminal).contains("3 years ago     6"));
+
+    app.process_events(&mut terminal, into_keys(b"M".iter()))?;
+    assert!(!rendered(&terminal).contains("3 years ago"));
+    Ok(())
+}
+
+#[test]
+fn it_summarizes_marked_entries_before_removing_them() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-02"])?;
+    // Make sure nothing is removed from the fixture, even if confirmation was skipped.
+    app.state.removal = RemovalMode::DryRun;
+
+    app.process_events(&mut terminal, into_keys(b" \t".iter()))?;
+    terminal.backend.resize(100, 20);
+    app.process_events(&mut terminal, vec![Event::Key(Key::Ctrl('r'))].into_iter())?;
+    let screen = rendered(&terminal);
+    assert!(screen.contains("Permanently delete 1 marked entries"));
+    assert!(screen.contains("sample-02"), "the marked paths are listed");
+    assert!(screen.contains("Type y or 1 and press <Enter> to confirm"));
+
+    app.process_events(&mut terminal, vec![Event::Key(Key::Esc)].into_iter())?;
+    assert!(!rendered(&terminal).contains("Confirm removal"));
+    assert!(app.state.deletion.is_none());
+    assert_eq!(
+        app.window.mark_pane.as_ref().map(|p| p.marked().len()),
+        Some(1),
+        "the entry is still marked"
+    );
+    Ok(())
+}
+
+#[test]
+fn it_draws_the_current_directory_as_a_treemap_when_toggled() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-01"])?;
+    terminal.backend.resize(100, 40);
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    assert!(
+        !rendered(&terminal).contains("Treemap"),
+        "hidden by default"
+    );
+
+    app.process_events(&mut terminal, into_keys(b"T".iter()))?;
+    let screen = rendered(&terminal);
+    assert!(screen.contains("Treemap of "));
+    assert!(
+        screen.contains("dir-a.1mb"),
+        "entries of directories are drawn within them"
+    );
+
+    app.process_events(&mut terminal, into_keys(b"l".iter()))?;
+    assert!(
+        rendered(&terminal).contains("sample-01/dir ─"),
+        "entering a directory draws its entries"
+    );
+
+    // when focussing the treemap
+    app.process_events(&mut terminal, into_keys(b"\t".iter()))?;
+    assert!(matches!(app.state.focussed, FocussedPane::Treemap));
+    let largest = app.state.selected;
+    app.process_events(&mut terminal, into_keys(b"l".iter()))?;
+    let neighbour = app.state.selected;
+    assert_ne!(neighbour, largest, "the entry to the right is selected");
+    app.process_events(&mut terminal, into_keys(b"h".iter()))?;
+    assert_eq!(app.state.selected, largest, "and the one to its left again");
+
+    app.process_events(&mut terminal, into_keys(b"u".iter()))?;
+    let dir = app.state.selected.expect("the directory that was left");
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    assert_eq!(app.state.root, dir, "entering a rectangle re-roots the map");
+    assert!(matches!(app.state.focussed, FocussedPane::Treemap));
+
+    app.process_events(&mut terminal, into_keys(b"T".iter()))?;
+    assert!(
+        matches!(app.state.focussed, FocussedPane::Main),
+        "hiding the treemap focusses the entries"
+    );
+    Ok(())
+}
+
+#[test]
+fn treemap_rectangles_cover_the_area_without_overlapping() {
+    use crate::interactive::widgets::squarify;
+    use tui::layout::Rect;
+
+    let area = Rect::new(3, 2, 60, 17);
+    let sizes = [500, 300, 100, 50, 30, 10, 7, 3];
+    let rects = squarify(&sizes, area);
+    assert_eq!(rects.len(), sizes.len());
+    assert_eq!(
+        rects.iter().map(|r| r.area()).sum::<u16>(),
+        area.area(),
+        "all cells are covered"
+    );
+    for (i, a) in rects.iter().enumerate() {
+        assert_eq!(a.intersection(area), *a, "{:?} is within the area", a);
+        for b in &rects[i + 1..] {
+            assert!(!a.intersects(*b), "{:?} and {:?} don't overlap", a, b);
+        }
+    }
+    assert!(
+        rects[0].area() > rects[1].area() && rects[1].area() > rects[2].area(),
+        "larger sizes cover more cells"
+    );
+    assert_eq!(squarify(&[1, 2], Rect::default()), vec![Rect::default(); 2]);
+}
+
+#[test]
+fn it_shows_details_of_the_selected_entry_when_toggled() -> Result<()> {
+    let (mut terminal, mut app) = initialized_app_and_terminal_from_fixture(&["sample-01"])?;
+    terminal.backend.resize(120, 40);
+    app.process_events(&mut terminal, into_keys(b"o".iter()))?;
+    assert!(
+        !rendered(&terminal).contains("Details"),
+        "hidden by default"
+    );
+
+    app.process_events(&mut terminal, into_keys(b"i".iter()))?;
+    let screen = rendered(&terminal);
+    assert!(screen.contains("Details"));
+    assert!(
+        screen.contains("Type         directory"),
+        "the largest entry, a directory, is selected"
+    );
+    assert!(screen.contains("Files        5"));
+    assert!(screen.contains("Directories  2"));
+    assert!(screen.contains("Largest      dir-a.1mb"));
+    assert!(
+        matches!(app.state.focussed, FocussedPane::Main),
+        "the selection can still be changed"
+    );
+
+    app.process_events(&mut terminal, into_keys(b"j".iter()))?;
+    let screen = rendered(&terminal);
+    assert!(screen.contains("Type         file"));
+    assert!(screen.contains("Apparent"));
+    assert!(screen.contains("Disk usage"));
+    assert!(
+        screen.contains(" UTC ("),
+        "times are shown as timestamp and age"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        assert!(screen.contains("Permissions"));
+        let selected = app.state.selected.expect("an entry is selected");
+        let uid = crate::interactive::path_of(&app.traversal.tree, selected)
+            .symlink_metadata()?
+            .uid();
+        assert!(
+            screen.contains(&format!("uid {uid}")),
+            "the owner is shown with its name, if known"
+        );
+    }
+
+    app.process_events(&mut terminal, into_keys(b"\t".iter()))?;
+    assert!(matches!(app.state.focussed, FocussedPane::Details));
+    app.process_events(&mut terminal, into_keys(b"\ti".iter()))?;
+    assert!(matches!(app.state.focussed, FocussedPane::Main));
+    assert!(!rendered(&terminal).contains("Details"));
+    Ok(())
+}
@@ -1,11 +1,11 @@
 use anyhow::{Context, Error, Result};
 use dua::{
-    traverse::{EntryData, Tree, TreeIndex},
+    traverse::{EntryData, EntryType, Tree, TreeIndex},
     ByteFormat, TraversalSorting, WalkOptions,
 };
 use itertools::Itertools;
 use jwalk::{DirEntry, WalkDir};
-use petgraph::prelude::NodeIndex;
+use petgraph::{prelude::NodeIndex, Direction};
 use std::{
     env::temp_dir,
     ffi::OsStr,
@@ -101,9 +101,7 @@ fn delete_recursive(path: impl AsRef<Path>) -> Result<()> {
                 .sorted_by_key(|p| p.components().count())
                 .rev()
                 .map(|d| {
-                    remove_dir(d)
-                        .with_context(|| format!("Could not delete '{}'", d.display()))
-                        .map_err(Error::from)
+                    remove_dir(d).with_context(|| format!("Could not delete '{}'", d.display()))
                 }),
         )
         .collect::<Result<_, _>>()
@@ -175,15 +173,7 @@ pub fn initialized_app_and_terminal_with_closure(
     let input_paths = fixture_paths.iter().map(|c| convert(c.as_ref())).collect();
     let app = TerminalApp::initialize(
         &mut terminal,
-        WalkOptions {
-            threads: 1,
-            byte_format: ByteFormat::Metric,
-            apparent_size: true,
-            count_hard_links: false,
-            sorting: TraversalSorting::AlphabeticalByFileName,
-            cross_filesystems: false,
-            ignore_dirs: Vec::new(),
-        },
+        walk_options(),
         input_paths,
         Interaction::None,
     )?
@@ -194,10 +184,35 @@ pub fn initialized_app_and_terminal_with_closure(
     ))
 }
 
+pub fn walk_options() -> WalkOptions {
+    WalkOptions {
+        threads: 1,
+        byte_format: ByteFormat::Metric,
+        apparent_size: true,
+        count_hard_links: false,
+        sorting: TraversalSorting::AlphabeticalByFileName,
+        cross_filesystems: false,
+        ignore_dirs: Vec::new(),
+        exclude: Default::default(),
+        respect_ignore_files: false,
+    }
+}
+
 pub fn new_test_terminal() -> std::io::Result<Terminal<TestBackend>> {
     Terminal::new(TestBackend::new(40, 20))
 }
 
+/// Return everything drawn to `terminal`, with all lines concatenated.
+pub fn rendered(terminal: &Terminal<TestBackend>) -> String {
+    terminal
+        .backend
+        .buffer()
+        .content
+        .iter()
+        .map(|cell| cell.symbol.as_str())
+        .collect()
+}
+
 pub fn initialized_app_and_terminal_from_paths(
     fixture_paths: &[PathBuf],
 ) -> Result<(Terminal<TestBackend>, TerminalApp), Error> {
@@ -217,6 +232,7 @@ pub fn initialized_app_and_terminal_from_fixture(
 
 pub fn sample_01_tree() -> Tree {
     let mut tree = Tree::new();
+    let mut symlinks = Vec::new();
     {
         let mut add_node = make_add_node(&mut tree);
         #[cfg(not(windows))]
@@ -231,9 +247,10 @@ pub fn sample_01_tree() -> Tree {
                 add_node("a", 256, Some(sn));
                 add_node("b.empty", 0, Some(sn));
                 #[cfg(not(windows))]
-                add_node("c.lnk", 1, Some(sn));
+                let ln = add_node("c.lnk", 1, Some(sn));
                 #[cfg(windows)]
-                add_node("c.lnk", 0, Some(sn));
+                let ln = add_node("c.lnk", 0, Some(sn));
+                symlinks.push(ln);
                 let dn = add_node("dir", 1258024, Some(sn));
                 {
                     add_node("1000bytes", 1000, Some(dn));
@@ -252,6 +269,9 @@ pub fn sample_01_tree() -> Tree {
             }
         }
     }
+    for idx in symlinks {
+        tree[idx].entry_type = EntryType::Symlink;
+    }
     tree
 }
 
@@ -294,15 +314,54 @@ pub fn make_add_node(t: &mut Tree) -> impl FnMut(&str, u128, Option<NodeIndex>)
         let n = t.add_node(EntryData {
             name: PathBuf::from(name),
             size,
-            metadata_io_error: false,
+            entry_type: EntryType::File,
+            ..Default::default()
         });
         if let Some(from) = maybe_from_idx {
             t.add_edge(from, n, ());
+            if !t[from].entry_type.is_dir() {
+                // The parent was counted as file by its ancestors so far.
+                t[from].entry_type = EntryType::Directory;
+                let mut ancestor = t.neighbors_directed(from, Direction::Incoming).next();
+                while let Some(idx) = ancestor {
+                    t[idx].file_count -= 1;
+                    ancestor = t.neighbors_directed(idx, Direction::Incoming).next();
+                }
+            }
+        }
+        let mut ancestor = maybe_from_idx;
+        while let Some(idx) = ancestor {
+            t[idx].entry_count += 1;
+            t[idx].file_count += 1;
+            ancestor = t.neighbors_directed(idx, Direction::Incoming).next();
         }
         n
     }
 }
 
+/// Return `tree` with all modification times removed, as they depend on when the fixtures were checked out.
+pub fn without_mtimes(tree: &Tree) -> Tree {
+    tree.map(
+        |_, entry| EntryData {
+            mtime: None,
+            ..entry.clone()
+        },
+        |_, edge| *edge,
+    )
+}
+
+/// Process no-op events until the marked entries were removed in the background.
+pub fn wait_for_deletion(
+    app: &mut TerminalApp,
+    terminal: &mut Terminal<TestBackend>,
+) -> Result<()> {
+    while app.state.deletion.is_some() {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        app.process_events(terminal, std::iter::empty())?;
+    }
+    Ok(())
+}
+
 pub fn debug(item: impl fmt::Debug) -> String {
     format!("{:?}", item)
 }
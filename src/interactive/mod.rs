@@ -8,7 +8,7 @@ mod utils {
         get_entry_or_panic,
         traverse::{Tree, TreeIndex},
     };
-    use std::path::PathBuf;
+    use std::path::{Component, Path, PathBuf};
 
     pub fn path_of(tree: &Tree, mut node_idx: TreeIndex) -> PathBuf {
         const THE_ROOT: usize = 1;
@@ -28,5 +28,74 @@ mod utils {
                 acc
             })
     }
+
+    /// Return the names of all entries from the top-level entry down to `node_idx`.
+    pub fn names_of(tree: &Tree, mut node_idx: TreeIndex) -> Vec<PathBuf> {
+        let mut names = Vec::new();
+        while let Some(parent_idx) = tree.neighbors_directed(node_idx, petgraph::Incoming).next() {
+            names.push(get_entry_or_panic(tree, node_idx).name.clone());
+            node_idx = parent_idx;
+        }
+        names.reverse();
+        names
+    }
+
+    /// Follow `names` from `node_idx` downwards for as long as there are entries with these names,
+    /// and return the last entry that was found.
+    pub fn node_by_names(
+        tree: &Tree,
+        mut node_idx: TreeIndex,
+        names: &[impl AsRef<Path>],
+    ) -> TreeIndex {
+        for name in names {
+            match tree
+                .neighbors_directed(node_idx, petgraph::Outgoing)
+                .find(|&idx| get_entry_or_panic(tree, idx).name == name.as_ref())
+            {
+                Some(idx) => node_idx = idx,
+                None => break,
+            }
+        }
+        node_idx
+    }
+
+    /// Return the entry at `path`, which is relative to `node_idx`, or starts with the path of one of the
+    /// top-level entries below `root_idx`.
+    pub fn node_by_path(
+        tree: &Tree,
+        node_idx: TreeIndex,
+        root_idx: TreeIndex,
+        path: &Path,
+    ) -> Option<TreeIndex> {
+        follow_path(tree, node_idx, path).or_else(|| {
+            tree.neighbors_directed(root_idx, petgraph::Outgoing)
+                .find_map(|top_level_idx| {
+                    let name = &get_entry_or_panic(tree, top_level_idx).name;
+                    let rest = match path.strip_prefix(name) {
+                        Ok(rest) => rest,
+                        Err(_) => path
+                            .strip_prefix(std::env::current_dir().ok()?.join(name))
+                            .ok()?,
+                    };
+                    follow_path(tree, top_level_idx, rest)
+                })
+        })
+    }
+
+    fn follow_path(tree: &Tree, mut node_idx: TreeIndex, path: &Path) -> Option<TreeIndex> {
+        for component in path.components() {
+            node_idx = match component {
+                Component::CurDir => node_idx,
+                Component::ParentDir => tree
+                    .neighbors_directed(node_idx, petgraph::Incoming)
+                    .next()?,
+                Component::Normal(name) => tree
+                    .neighbors_directed(node_idx, petgraph::Outgoing)
+                    .find(|&idx| get_entry_or_panic(tree, idx).name == name)?,
+                Component::RootDir | Component::Prefix(_) => return None,
+            };
+        }
+        Some(node_idx)
+    }
 }
-pub use utils::path_of;
+pub use utils::{names_of, node_by_names, node_by_path, path_of};
@@ -0,0 +1,140 @@
+use crate::interactive::widgets::{EntryMarkMap, MarkMode};
+use dua::ByteFormat;
+use std::{borrow::Borrow, path::Path};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Widget, Wrap},
+};
+
+/// The amount of marked entries listed by path, largest first.
+const NUM_LISTED_ENTRIES: usize = 5;
+
+/// A popup in the middle of the screen summarizing what is about to be removed.
+pub struct RemovalConfirmation;
+
+pub struct RemovalConfirmationProps<'a> {
+    pub mode: MarkMode,
+    /// The directory to move entries into if `mode` is [`MarkMode::Move`], or the archive to write them into.
+    pub destination: Option<&'a Path>,
+    pub marked: &'a EntryMarkMap,
+    pub format: ByteFormat,
+    pub dry_run: bool,
+}
+
+impl RemovalConfirmation {
+    pub fn render<'a>(
+        &self,
+        props: impl Borrow<RemovalConfirmationProps<'a>>,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let RemovalConfirmationProps {
+            mode,
+            destination,
+            marked,
+            format,
+            dry_run,
+        } = props.borrow();
+
+        let total_bytes = format.display(marked.values().map(|m| m.size).sum::<u128>());
+        let num_marked = marked.len();
+        let question = match mode {
+            MarkMode::Delete => {
+                format!("Permanently delete {num_marked} marked entries ({total_bytes})?")
+            }
+            MarkMode::Move => format!(
+                "Move {num_marked} marked entries ({total_bytes}) into '{}'?",
+                destination.unwrap_or_else(|| Path::new("?")).display()
+            ),
+            #[cfg(feature = "trash-move")]
+            MarkMode::Trash => {
+                format!("Move {num_marked} marked entries ({total_bytes}) to the trash bin?")
+            }
+            #[cfg(feature = "archive")]
+            MarkMode::Archive { .. } => format!(
+                "Archive {num_marked} marked entries ({total_bytes}) into '{}'?",
+                destination.unwrap_or_else(|| Path::new("?")).display()
+            ),
+        };
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![Spans::from(Span::styled(question, bold))];
+        if *dry_run {
+            lines.push(Spans::from("Nothing will be removed from disk (dry run)."));
+        }
+        lines.push(Spans::from(""));
+
+        let mut largest: Vec<_> = marked.values().collect();
+        largest.sort_by_key(|mark| std::cmp::Reverse(mark.size));
+        for mark in largest.iter().take(NUM_LISTED_ENTRIES) {
+            lines.push(Spans::from(vec![
+                Span::styled(
+                    format!(
+                        "{:>width$} ",
+                        format.display(mark.size).to_string(),
+                        width = format.width()
+                    ),
+                    Style {
+                        fg: Color::Green.into(),
+                        ..Style::default()
+                    },
+                ),
+                Span::from(mark.path.display().to_string()),
+            ]));
+        }
+        if num_marked > NUM_LISTED_ENTRIES {
+            lines.push(Spans::from(format!(
+                "… and {} more",
+                num_marked - NUM_LISTED_ENTRIES
+            )));
+        }
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(vec![
+            Span::from("Type "),
+            Span::styled("y", bold),
+            Span::from(" or "),
+            Span::styled(num_marked.to_string(), bold),
+            Span::from(" and press <Enter> to confirm, <Esc> to cancel."),
+        ]));
+        #[cfg(feature = "archive")]
+        if let MarkMode::Archive { .. } = mode {
+            lines.push(Spans::from(vec![
+                Span::from("Type "),
+                Span::styled("delete", bold),
+                Span::from(" to also delete the entries once the archive was verified."),
+            ]));
+        }
+
+        let popup_area = centered(area, 80, lines.len() as u16 + 2);
+        let block = Block::default()
+            .title(match mode {
+                #[cfg(feature = "archive")]
+                MarkMode::Archive { .. } => " Confirm archiving ",
+                _ => " Confirm removal ",
+            })
+            .borders(Borders::ALL)
+            .border_style(Style {
+                fg: Color::LightRed.into(),
+                ..bold
+            });
+        Clear.render(popup_area, buf);
+        Paragraph::new(Text::from(lines))
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(popup_area, buf);
+    }
+}
+
+/// Return an area of at most `width` and `height` in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
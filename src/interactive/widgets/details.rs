@@ -0,0 +1,256 @@
+use crate::interactive::{format_age, format_timestamp, path_of, CursorDirection};
+use crosstermion::{input::Key, input::Key::*};
+use dua::{
+    traverse::{EntryType, Tree, TreeIndex},
+    ByteFormat,
+};
+use filesize::PathExt;
+use petgraph::Direction;
+use std::{borrow::Borrow, fs::Metadata, path::PathBuf, time::SystemTime};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+
+/// The width of the column with the name of each detail.
+const LABEL_WIDTH: usize = 13;
+
+/// The metadata of an entry as read from disk, which isn't kept in the tree.
+struct FetchedMetadata {
+    index: TreeIndex,
+    path: PathBuf,
+    metadata: Result<Metadata, String>,
+    /// The names of the user and group owning the entry, along with their ids.
+    #[cfg(unix)]
+    owner: String,
+}
+
+/// Everything known about the selected entry, with metadata read from disk only once it's shown.
+#[derive(Default)]
+pub struct DetailsPane {
+    pub scroll: u16,
+    fetched: Option<FetchedMetadata>,
+}
+
+pub struct DetailsPaneProps<'a> {
+    pub tree: &'a Tree,
+    pub selected: Option<TreeIndex>,
+    pub format: ByteFormat,
+    pub border_style: Style,
+}
+
+impl DetailsPane {
+    pub fn process_events(&mut self, key: Key) {
+        let direction = match key {
+            Char('H') => CursorDirection::ToTop,
+            Char('G') => CursorDirection::ToBottom,
+            Ctrl('u') | PageUp => CursorDirection::PageUp,
+            Char('k') | Up => CursorDirection::Up,
+            Char('j') | Down => CursorDirection::Down,
+            Ctrl('d') | PageDown => CursorDirection::PageDown,
+            _ => return,
+        };
+        self.scroll = direction.move_cursor(self.scroll as usize) as u16;
+    }
+
+    /// Return the metadata of the entry at `index`, reading it from disk unless it was already read.
+    fn metadata(&mut self, tree: &Tree, index: TreeIndex) -> &FetchedMetadata {
+        let path = path_of(tree, index);
+        let is_outdated = match &self.fetched {
+            Some(fetched) => fetched.index != index || fetched.path != path,
+            None => true,
+        };
+        if is_outdated {
+            self.scroll = 0;
+            let metadata = path.symlink_metadata().map_err(|err| err.to_string());
+            self.fetched = Some(FetchedMetadata {
+                index,
+                #[cfg(unix)]
+                owner: metadata.as_ref().map(owner).unwrap_or_default(),
+                metadata,
+                path,
+            });
+        }
+        self.fetched.as_ref().expect("just fetched")
+    }
+
+    pub fn render<'a>(
+        &mut self,
+        props: impl Borrow<DetailsPaneProps<'a>>,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let DetailsPaneProps {
+            tree,
+            selected,
+            format,
+            border_style,
+        } = props.borrow();
+        let block = Block::default()
+            .title("Details")
+            .border_style(*border_style)
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let index = match selected.filter(|idx| tree.node_weight(*idx).is_some()) {
+            Some(index) => index,
+            None => {
+                Paragraph::new("No entry is selected").render(inner, buf);
+                return;
+            }
+        };
+        let mut lines = Vec::new();
+        let mut detail = |label: &str, value: String| {
+            lines.push(Spans::from(vec![
+                Span::styled(
+                    format!("{label:<LABEL_WIDTH$}"),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(value),
+            ]));
+        };
+        let entry = &tree[index];
+        let fetched = self.metadata(tree, index);
+        let (path, metadata) = (&fetched.path, &fetched.metadata);
+        detail("Path", path.display().to_string());
+        detail(
+            "Type",
+            match entry.entry_type {
+                EntryType::File => "file",
+                EntryType::Directory => "directory",
+                EntryType::Symlink => "symlink",
+                EntryType::Other => "other",
+                EntryType::Missing => "missing",
+            }
+            .into(),
+        );
+        detail("Size", format.display(entry.size).to_string());
+
+        if entry.entry_type.is_dir() {
+            detail("Files", entry.file_count.to_string());
+            detail(
+                "Directories",
+                (entry.entry_count - entry.file_count).to_string(),
+            );
+            let largest = tree
+                .neighbors_directed(index, Direction::Outgoing)
+                .max_by_key(|idx| tree[*idx].size)
+                .map(|idx| &tree[idx]);
+            detail(
+                "Largest",
+                largest.map_or_else(
+                    || "-".into(),
+                    |child| format!("{} {}", child.name.display(), format.display(child.size)),
+                ),
+            );
+        }
+
+        match metadata {
+            Ok(metadata) => {
+                if !metadata.is_dir() {
+                    detail(
+                        "Apparent",
+                        format.display(metadata.len() as u128).to_string(),
+                    );
+                    detail(
+                        "Disk usage",
+                        path.size_on_disk_fast(metadata).map_or_else(
+                            |err| err.to_string(),
+                            |size| format.display(size as u128).to_string(),
+                        ),
+                    );
+                }
+                let now = SystemTime::now();
+                let age = |time: std::io::Result<SystemTime>| {
+                    time.map_or_else(
+                        |_| "unknown".into(),
+                        |time| format!("{} ({})", format_timestamp(time), format_age(time, now)),
+                    )
+                };
+                detail("Modified", age(metadata.modified()));
+                detail("Accessed", age(metadata.accessed()));
+                #[cfg(unix)]
+                {
+                    use std::{convert::TryFrom, os::unix::fs::MetadataExt};
+                    let changed = u64::try_from(metadata.ctime())
+                        .map(|secs| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                        .map_err(|_| std::io::ErrorKind::InvalidData.into());
+                    detail("Changed", age(changed));
+                    detail("Owner", fetched.owner.clone());
+                    detail("Permissions", permissions(metadata.mode()));
+                    detail("Inode", metadata.ino().to_string());
+                    detail("Hard links", metadata.nlink().to_string());
+                    detail("Device", metadata.dev().to_string());
+                }
+                #[cfg(not(unix))]
+                {
+                    detail("Created", age(metadata.created()));
+                    detail(
+                        "Read-only",
+                        if metadata.permissions().readonly() {
+                            "yes".into()
+                        } else {
+                            "no".into()
+                        },
+                    );
+                }
+            }
+            Err(err) => detail("Metadata", format!("could not be read: {err}")),
+        }
+
+        Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .render(inner, buf);
+    }
+}
+
+/// Return the names of the user and group owning the entry with `metadata`, each followed by its id.
+#[cfg(unix)]
+fn owner(metadata: &Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+    let (uid, gid) = (metadata.uid(), metadata.gid());
+    let named = |database: &str, kind: &str, id: u32| match name_of(database, id) {
+        Some(name) => format!("{name} ({kind} {id})"),
+        None => format!("{kind} {id}"),
+    };
+    format!(
+        "{} / {}",
+        named("/etc/passwd", "uid", uid),
+        named("/etc/group", "gid", gid)
+    )
+}
+
+/// Return the name of `id` in `database`, like `/etc/passwd`, which has the name in the first and the id in the
+/// third field of each line.
+#[cfg(unix)]
+fn name_of(database: &str, id: u32) -> Option<String> {
+    std::fs::read_to_string(database)
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            (fields.nth(1)?.parse::<u32>().ok()? == id).then(|| name.to_owned())
+        })
+}
+
+/// Return `mode` like `ls -l` does, followed by its octal value.
+#[cfg(unix)]
+fn permissions(mode: u32) -> String {
+    const KINDS: [char; 3] = ['r', 'w', 'x'];
+    let symbolic: String = (0..9)
+        .map(|bit| {
+            if mode & (1 << (8 - bit)) != 0 {
+                KINDS[bit % 3]
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{symbolic} ({:o})", mode & 0o7777)
+}
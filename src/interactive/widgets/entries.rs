@@ -1,11 +1,16 @@
 use crate::interactive::{
-    path_of,
+    format_age, format_count, path_of,
     widgets::{entry_color, EntryMarkMap},
-    DisplayOptions, EntryDataBundle,
+    DisplayOptions, EntryDataBundle, SortMode, AGE_COLUMN_WIDTH, COUNT_COLUMN_WIDTH,
 };
-use dua::traverse::{Tree, TreeIndex};
+use dua::traverse::{Traversal, Tree, TreeIndex};
 use itertools::Itertools;
-use std::{borrow::Borrow, path::Path};
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    path::Path,
+    time::SystemTime,
+};
 use tui::{
     buffer::Buffer,
     layout::Rect,
@@ -29,6 +34,13 @@ pub struct EntriesProps<'a> {
     pub marked: Option<&'a EntryMarkMap>,
     pub border_style: Style,
     pub is_focussed: bool,
+    /// If set, show how much each entry changed in size compared to its counterpart in this traversal.
+    pub baseline: Option<&'a Traversal>,
+    /// The pattern the entries were filtered with, if any.
+    pub filter: Option<&'a str>,
+    pub sorting: SortMode,
+    /// If set, entries are shown as a tree, and these are the directories whose entries are shown below them.
+    pub expanded: Option<&'a HashSet<TreeIndex>>,
 }
 
 #[derive(Default)]
@@ -52,6 +64,10 @@ impl Entries {
             marked,
             border_style,
             is_focussed,
+            baseline,
+            filter,
+            sorting,
+            expanded,
         } = props.borrow();
         let list = &mut self.list;
 
@@ -61,7 +77,24 @@ impl Entries {
                 .is_none()
         };
 
-        let total: u128 = entries.iter().map(|b| b.data.size).sum();
+        let parent_of = |node_idx| tree.neighbors_directed(node_idx, petgraph::Incoming).next();
+        // The amount of directories between each entry and the current directory.
+        let depths: Vec<usize> = entries
+            .iter()
+            .map(|b| match expanded {
+                Some(_) => std::iter::successors(parent_of(b.index), |idx| parent_of(*idx))
+                    .take_while(|idx| idx != root)
+                    .count(),
+                None => 0,
+            })
+            .collect();
+        let num_top_level = depths.iter().filter(|depth| **depth == 0).count();
+        let total: u128 = entries
+            .iter()
+            .zip(&depths)
+            .filter(|(_, depth)| **depth == 0)
+            .map(|(b, _)| b.data.size)
+            .sum();
         let title = match path_of(tree, *root).to_string_lossy().to_string() {
             ref p if p.is_empty() => Path::new(".")
                 .canonicalize()
@@ -69,15 +102,26 @@ impl Entries {
                 .unwrap_or_else(|_| String::from(".")),
             p => p,
         };
-        let title = format!(
-            " {} ({} item{}) ",
-            title,
-            entries.len(),
-            match entries.len() {
-                1 => "",
-                _ => "s",
-            }
-        );
+        let title = match filter {
+            Some(pattern) => format!(
+                " {} ({} of {} items matching '{}', {}) ",
+                title,
+                num_top_level,
+                tree.neighbors_directed(*root, petgraph::Outgoing).count(),
+                pattern,
+                sorting.label()
+            ),
+            None => format!(
+                " {} ({} item{}, {}) ",
+                title,
+                num_top_level,
+                match num_top_level {
+                    1 => "",
+                    _ => "s",
+                },
+                sorting.label()
+            ),
+        };
         let block = Block::default()
             .title(title.as_str())
             .border_style(*border_style)
@@ -90,17 +134,34 @@ impl Entries {
                 .unwrap_or(0)
         });
 
+        let baseline_sizes: Option<HashMap<&Path, u128>> = baseline.map(|baseline| {
+            dua::matching_node(tree, *root, &baseline.tree, baseline.root_index)
+                .map(|baseline_root| {
+                    baseline
+                        .tree
+                        .neighbors_directed(baseline_root, petgraph::Outgoing)
+                        .filter_map(|idx| baseline.tree.node_weight(idx))
+                        .map(|w| (w.name.as_path(), w.size))
+                        .collect()
+                })
+                .unwrap_or_default()
+        });
+
+        let now = SystemTime::now();
         let props = ListProps {
             block: Some(block),
             entry_in_view,
         };
-        let lines = entries.iter().map(
-            |EntryDataBundle {
-                 index: node_idx,
-                 data: w,
-                 is_dir,
-                 exists,
-             }| {
+        let lines = entries.iter().zip(&depths).map(
+            |(
+                EntryDataBundle {
+                    index: node_idx,
+                    data: w,
+                    is_dir,
+                    exists,
+                },
+                depth,
+            )| {
                 let mut style = Style::default();
                 let is_selected = if let Some(idx) = selected {
                     *idx == *node_idx
@@ -125,7 +186,59 @@ impl Entries {
                         ..style
                     },
                 );
-                let fraction = w.size as f32 / total as f32;
+                let delta = baseline_sizes.as_ref().map(|sizes| {
+                    let baseline_size = match baseline.filter(|_| *depth != 0) {
+                        None => sizes.get(w.name.as_path()).copied(),
+                        Some(baseline) => {
+                            dua::matching_node(tree, *node_idx, &baseline.tree, baseline.root_index)
+                                .and_then(|idx| baseline.tree.node_weight(idx))
+                                .map(|b| b.size)
+                        }
+                    };
+                    let delta = w.size as i128 - baseline_size.unwrap_or(0) as i128;
+                    Span::styled(
+                        if delta == 0 {
+                            format!(" {:>width$}", "", width = display.byte_format.width() + 1)
+                        } else {
+                            format!(" {}", dua::format_delta(display.byte_format, delta))
+                        },
+                        Style {
+                            fg: if delta > 0 { Color::Red } else { Color::Green }.into(),
+                            ..style
+                        },
+                    )
+                });
+                let mtime = display.show_mtime.then(|| {
+                    Span::styled(
+                        format!(
+                            " {:>width$}",
+                            w.mtime
+                                .map(|mtime| format_age(mtime, now))
+                                .unwrap_or_default(),
+                            width = AGE_COLUMN_WIDTH
+                        ),
+                        style,
+                    )
+                });
+                let entry_count = display.show_entry_count.then(|| {
+                    Span::styled(
+                        format!(
+                            " {:>width$}",
+                            if *is_dir {
+                                format_count(w.file_count)
+                            } else {
+                                String::new()
+                            },
+                            width = COUNT_COLUMN_WIDTH
+                        ),
+                        style,
+                    )
+                });
+                // Entries of expanded directories are compared to the size of their directory.
+                let fraction = match parent_of(*node_idx) {
+                    Some(parent_idx) if *depth != 0 => w.size as f32 / tree[parent_idx].size as f32,
+                    _ => w.size as f32 / total as f32,
+                };
                 let should_avoid_showing_a_big_reversed_bar = fraction > 0.9;
                 let local_style = if should_avoid_showing_a_big_reversed_bar {
                     style.remove_modifier(Modifier::REVERSED)
@@ -143,8 +256,21 @@ impl Entries {
                 let name = Span::styled(
                     fill_background_to_right(
                         format!(
-                            "{prefix}{}",
+                            "{indent}{prefix}{}",
                             w.name.to_string_lossy(),
+                            indent = match expanded {
+                                Some(expanded) => format!(
+                                    "{:indent$}{}",
+                                    "",
+                                    match expanded.contains(node_idx) {
+                                        true => "▾",
+                                        false if *is_dir && w.entry_count != 0 => "▸",
+                                        false => " ",
+                                    },
+                                    indent = 2 * depth
+                                ),
+                                None => String::new(),
+                            },
                             prefix = if *is_dir && !is_top(*root) { "/" } else { " " }
                         ),
                         area.width,
@@ -160,7 +286,12 @@ impl Entries {
                         Style { fg, ..style }
                     },
                 );
-                vec![bytes, left_bar, percentage, right_bar, name]
+                std::iter::once(bytes)
+                    .chain(delta)
+                    .chain(mtime)
+                    .chain(entry_count)
+                    .chain([left_bar, percentage, right_bar, name])
+                    .collect::<Vec<_>>()
             },
         );
 
@@ -18,6 +18,8 @@ pub struct FooterProps {
     pub elapsed: Option<std::time::Duration>,
     pub format: ByteFormat,
     pub message: Option<String>,
+    /// The total size of all entries matching the filter, if one is set.
+    pub matching_bytes: Option<u128>,
 }
 
 impl Footer {
@@ -29,6 +31,7 @@ impl Footer {
             traversal_start,
             format,
             message,
+            matching_bytes,
         } = props.borrow();
 
         let spans = vec![
@@ -52,6 +55,7 @@ impl Footer {
                 }
             ))
             .into(),
+            matching_bytes.map(|b| Span::from(format!("Matching: {}  ", format.display(b)))),
             message.as_ref().map(|m| {
                 Span::styled(
                     m,
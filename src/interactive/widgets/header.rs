@@ -1,3 +1,8 @@
+use dua::{
+    traverse::{Tree, TreeIndex},
+    ByteFormat,
+};
+use std::borrow::Borrow;
 use tui::{
     buffer::Buffer,
     layout::Rect,
@@ -5,48 +10,128 @@ use tui::{
     text::{Span, Spans, Text},
     widgets::{Paragraph, Widget},
 };
+use tui_react::{
+    draw_text_nowrap_fn,
+    util::{block_width, rect},
+};
 
+/// The highest number shown next to an ancestor of the current directory, to be typed to jump to it.
+pub const MAX_ANCESTOR_KEY: usize = 9;
+
+pub struct HeaderProps<'a> {
+    pub bg_color: Color,
+    pub tree: &'a Tree,
+    /// The current directory.
+    pub root: TreeIndex,
+    /// The size of all entries, used for the top-level directory.
+    pub total_bytes: Option<u128>,
+    pub format: ByteFormat,
+}
+
+/// The path to the current directory, with the size of each directory on the way and the number of levels
+/// it is above the current one.
 pub struct Header;
 
 impl Header {
-    pub fn render(&self, bg_color: Color, area: Rect, buf: &mut Buffer) {
+    pub fn render<'a>(&self, props: impl Borrow<HeaderProps<'a>>, area: Rect, buf: &mut Buffer) {
+        let HeaderProps {
+            bg_color,
+            tree,
+            root,
+            total_bytes,
+            format,
+        } = props.borrow();
         let standard = Style {
             fg: Color::Black.into(),
-            bg: bg_color.into(),
+            bg: (*bg_color).into(),
             ..Default::default()
         };
         debug_assert_ne!(standard.bg, standard.fg);
-        let modified = |text: &'static str, modifier| {
-            Span::styled(
-                text,
-                Style {
-                    add_modifier: modifier,
-                    ..standard
-                },
-            )
+        let bold = Style {
+            add_modifier: Modifier::BOLD,
+            ..standard
         };
-        let bold = |text: &'static str| modified(text, Modifier::BOLD);
-        let italic = |text: &'static str| modified(text, Modifier::UNDERLINED);
-        let text = |text: &'static str| Span::styled(text, standard);
 
-        let spans = vec![
-            bold(" D"),
-            text("isk "),
-            bold("U"),
-            text("sage "),
-            bold("A"),
-            text("nalyzer v"),
-            text(env!("CARGO_PKG_VERSION")),
-            text("    "),
-            italic("(press "),
-            modified("?", Modifier::BOLD | Modifier::UNDERLINED),
-            italic(" for help)"),
-        ];
+        let ancestors: Vec<_> = std::iter::successors(Some(*root), |idx| {
+            tree.neighbors_directed(*idx, petgraph::Incoming).next()
+        })
+        .collect();
+        let mut crumbs: Vec<Vec<Span>> = ancestors
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(levels_up, idx)| {
+                let entry = &tree[*idx];
+                let is_top = levels_up + 1 == ancestors.len();
+                let name = if is_top {
+                    ".".into()
+                } else {
+                    entry.name.to_string_lossy()
+                };
+                let size = match total_bytes {
+                    Some(total_bytes) if is_top => *total_bytes,
+                    _ => entry.size,
+                };
+                let mut crumb = Vec::with_capacity(3);
+                if (1..=MAX_ANCESTOR_KEY).contains(&levels_up) {
+                    crumb.push(Span::styled(format!("[{levels_up}]"), bold));
+                }
+                crumb.push(Span::styled(
+                    name.into_owned(),
+                    if levels_up == 0 { bold } else { standard },
+                ));
+                crumb.push(Span::styled(format!(" {}", format.display(size)), standard));
+                crumb
+            })
+            .collect();
+
+        const SEPARATOR: &str = " › ";
+        let width = |crumbs: &[Vec<Span>]| {
+            crumbs
+                .iter()
+                .flatten()
+                .map(|span| block_width(&span.content))
+                .sum::<u16>()
+                + (block_width(SEPARATOR) * crumbs.len() as u16)
+        };
+        // Leave out the topmost directories until the path fits, but always show the current one.
+        let mut is_truncated = false;
+        while crumbs.len() > 1 && width(&crumbs) + 2 > area.width {
+            crumbs.remove(0);
+            is_truncated = true;
+        }
+        let mut spans = vec![Span::styled(if is_truncated { " …" } else { "" }, standard)];
+        for (i, crumb) in crumbs.into_iter().enumerate() {
+            if i != 0 || is_truncated {
+                spans.push(Span::styled(SEPARATOR, standard));
+            } else {
+                spans.push(Span::styled(" ", standard));
+            }
+            spans.extend(crumb);
+        }
+        let path_width = spans
+            .iter()
+            .map(|span| block_width(&span.content))
+            .sum::<u16>();
         Paragraph::new(Text::from(Spans::from(spans)))
             .style(Style {
-                bg: bg_color.into(),
+                bg: (*bg_color).into(),
                 ..Default::default()
             })
             .render(area, buf);
+
+        let help_text = " (press ? for help) ";
+        let help_text_width = block_width(help_text);
+        if path_width + help_text_width < area.width {
+            draw_text_nowrap_fn(
+                rect::snap_to_right(area, help_text_width),
+                buf,
+                help_text,
+                |_, _, _| Style {
+                    add_modifier: Modifier::UNDERLINED,
+                    ..standard
+                },
+            );
+        }
     }
 }
@@ -1,4 +1,4 @@
-use crate::interactive::CursorDirection;
+use crate::interactive::{CursorDirection, RemovalMode};
 use crosstermion::{input::Key, input::Key::*};
 use std::{borrow::Borrow, cell::RefCell};
 use tui::{
@@ -21,6 +21,8 @@ pub struct HelpPane {
 pub struct HelpPaneProps {
     pub border_style: Style,
     pub has_focus: bool,
+    pub removal: RemovalMode,
+    pub skip_confirmation: bool,
 }
 
 fn margin(r: Rect, margin: u16) -> Rect {
@@ -49,6 +51,13 @@ impl HelpPane {
     }
 
     pub fn render(&mut self, props: impl Borrow<HelpPaneProps>, area: Rect, buf: &mut Buffer) {
+        let HelpPaneProps {
+            border_style,
+            has_focus,
+            removal,
+            skip_confirmation,
+        } = props.borrow();
+
         let lines = {
             let lines = RefCell::new(Vec::<Spans>::with_capacity(30));
             let add_newlines = |n| {
@@ -108,6 +117,11 @@ impl HelpPane {
                     Some("Activate 'Marked Items' pane to delete selected files."),
                 );
                 hotkey("?", "Show or hide the help pane", None);
+                hotkey(
+                    "i",
+                    "Show or hide the details of the selected entry",
+                    Some("Like its permissions, owner and the amount of entries in it"),
+                );
                 spacer();
             }
             title("Keys for Navigation");
@@ -122,22 +136,74 @@ impl HelpPane {
                     None,
                 );
                 hotkey("<Backspace>", "^", None);
+                hotkey(
+                    "1-9",
+                    "ascent as many levels at once",
+                    Some("The numbers are shown next to the directories in the header"),
+                );
                 hotkey("Ctrl + d", "move down 10 entries at once", None);
                 hotkey("<Page Down>", "^", None);
                 hotkey("Ctrl + u", "move up 10 entries at once", None);
                 hotkey("<Page Up>", "^", None);
                 hotkey("H/<Home>", "Move to the top of the entries list", None);
                 hotkey("G/<End>", "Move to the bottom of the entries list", None);
+                hotkey(
+                    "/",
+                    "Show only entries matching the typed text or glob",
+                    Some("<Enter> keeps the filter, <Esc> removes it"),
+                );
+                hotkey("n/N", "Move to the next/previous matching entry", None);
+                hotkey(
+                    ":",
+                    "Go to the entry at the typed relative or absolute path",
+                    Some("<Tab> completes the name of an entry"),
+                );
                 spacer();
             }
             title("Keys for display");
             {
                 hotkey("s", "toggle sort by size ascending/descending", None);
+                hotkey("A", "toggle sort by name ascending/descending", None);
+                hotkey(
+                    "c",
+                    "toggle sort by amount of contained entries descending/ascending",
+                    None,
+                );
+                hotkey(
+                    "m",
+                    "toggle sort by modification time descending/ascending",
+                    Some("Directories use the most recent time of all their entries"),
+                );
                 hotkey(
                     "g",
                     "cycle through percentage display and bar options",
                     None,
                 );
+                hotkey(
+                    "M",
+                    "toggle the column showing when entries were last modified",
+                    None,
+                );
+                hotkey(
+                    "C",
+                    "toggle the column showing the amount of files in directories",
+                    Some("Files at all depths are counted, but not directories"),
+                );
+                hotkey(
+                    "T",
+                    "toggle the treemap of the current directory below the entries",
+                    Some("Entries are drawn with an area proportional to their size"),
+                );
+                hotkey(
+                    "h/j/k/l",
+                    "select the entry next to the selected one in the treemap",
+                    Some("Once it's focussed with <Tab>. o/<Enter> enters the entry"),
+                );
+                hotkey(
+                    "t",
+                    "toggle showing entries as a tree with expandable directories",
+                    Some("o/l/<Right> expands a directory, u/h/<Left> collapses it"),
+                );
                 spacer();
             }
             title("Keys for entry operations");
@@ -159,6 +225,18 @@ impl HelpPane {
                 );
                 hotkey("<Space>", "Toggle the currently selected entry", None);
                 hotkey("a", "Toggle all entries", None);
+                hotkey(
+                    "r",
+                    "Rescan the selected entry to update its size",
+                    Some("Marked entries within it are unmarked"),
+                );
+                hotkey("R", "Rescan all entries", None);
+                #[cfg(feature = "trash-move")]
+                hotkey(
+                    "Ctrl + z",
+                    "Restore all entries moved to the trash bin",
+                    Some("Only entries trashed since dua was started are restored"),
+                );
                 spacer();
             }
             title("Keys in the Mark pane");
@@ -169,17 +247,56 @@ impl HelpPane {
                     None,
                 );
                 hotkey("a", "Remove all entries from the list", None);
-                hotkey(
-                    "Ctrl + r",
-                    "Permanently delete all marked entries without prompt!",
-                    Some("This operation cannot be undone!"),
-                );
+                match removal {
+                    RemovalMode::ReadOnly => {}
+                    RemovalMode::Enabled if *skip_confirmation => hotkey(
+                        "Ctrl + r",
+                        "Permanently delete all marked entries without prompt!",
+                        Some("This operation cannot be undone!"),
+                    ),
+                    RemovalMode::Enabled => hotkey(
+                        "Ctrl + r",
+                        "Permanently delete all marked entries after confirmation",
+                        Some("This operation cannot be undone!"),
+                    ),
+                    RemovalMode::DryRun => hotkey(
+                        "Ctrl + r",
+                        "Pretend to delete all marked entries",
+                        Some("Nothing is removed from disk (dry run)"),
+                    ),
+                }
+                if *removal != RemovalMode::ReadOnly {
+                    hotkey(
+                        "Ctrl + o",
+                        "Move all marked entries into another directory",
+                        Some("The directory is asked for first"),
+                    );
+                }
+                #[cfg(feature = "archive")]
+                if *removal != RemovalMode::ReadOnly {
+                    hotkey(
+                        "Ctrl + a",
+                        "Write all marked entries into a .tar.zst or .tar.gz archive",
+                        Some("Entries are deleted afterwards if 'delete' is typed to confirm"),
+                    );
+                }
                 #[cfg(feature = "trash-move")]
-                hotkey(
-                    "Ctrl + t",
-                    "Move all marked entries to the trash bin",
-                    Some("The entries can be restored from the trash bin"),
-                );
+                if *removal != RemovalMode::ReadOnly {
+                    hotkey(
+                        "Ctrl + t",
+                        "Move all marked entries to the trash bin",
+                        Some("The entries can be restored from the trash bin"),
+                    );
+                }
+                if *removal == RemovalMode::Enabled {
+                    hotkey(
+                        "Ctrl + x",
+                        "Stop removing marked entries",
+                        Some(
+                            "Works from any pane. Entries that were already removed stay removed.",
+                        ),
+                    );
+                }
                 spacer();
             }
             title("Keys for application control");
@@ -194,11 +311,6 @@ impl HelpPane {
             lines.into_inner()
         };
 
-        let HelpPaneProps {
-            border_style,
-            has_focus,
-        } = props.borrow();
-
         let title = "Help";
         let block = Block::default()
             .title(title)
@@ -1,9 +1,10 @@
 use crate::interactive::{
     widgets::{
-        Entries, EntriesProps, Footer, FooterProps, Header, HelpPane, HelpPaneProps, MarkPane,
-        MarkPaneProps, COLOR_MARKED,
+        DeletionStatus, DetailsPane, DetailsPaneProps, Entries, EntriesProps, Footer, FooterProps,
+        Header, HeaderProps, HelpPane, HelpPaneProps, MarkPane, MarkPaneProps, PromptLine,
+        RemovalConfirmation, RemovalConfirmationProps, TreemapPane, TreemapPaneProps, COLOR_MARKED,
     },
-    AppState, DisplayOptions, FocussedPane,
+    AppState, DisplayOptions, FocussedPane, Prompt, PromptKind, RemovalMode,
 };
 use dua::traverse::Traversal;
 use std::borrow::Borrow;
@@ -25,7 +26,10 @@ pub struct MainWindowProps<'a> {
 #[derive(Default)]
 pub struct MainWindow {
     pub help_pane: Option<HelpPane>,
+    pub details_pane: Option<DetailsPane>,
     pub entries_pane: Entries,
+    /// The entries of the current directory drawn as treemap below the list of entries, if shown.
+    pub treemap: Option<TreemapPane>,
     pub mark_pane: Option<MarkPane>,
 }
 
@@ -50,7 +54,7 @@ impl MainWindow {
             state,
         } = props.borrow();
 
-        let (entries_style, help_style, mark_style) = {
+        let (entries_style, treemap_style, help_style, details_style, mark_style) = {
             let grey = Style {
                 fg: Color::DarkGray.into(),
                 bg: Color::Reset.into(),
@@ -59,69 +63,129 @@ impl MainWindow {
             };
             let bold = Style::default().add_modifier(Modifier::BOLD);
             match state.focussed {
-                Main => (bold, grey, grey),
-                Help => (grey, bold, grey),
-                Mark => (grey, grey, bold),
+                Main => (bold, grey, grey, grey, grey),
+                Treemap => (grey, bold, grey, grey, grey),
+                Help => (grey, grey, bold, grey, grey),
+                Details => (grey, grey, grey, bold, grey),
+                Mark => (grey, grey, grey, grey, bold),
             }
         };
 
-        let (header_area, entries_area, footer_area) = {
+        let (header_area, entries_area, prompt_area, footer_area) = {
+            let prompt_height = if state.prompt.is_some() { 1 } else { 0 };
             let regions = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Length(1), Max(256), Length(1)].as_ref())
+                .constraints([Length(1), Max(256), Length(prompt_height), Length(1)].as_ref())
                 .split(area);
-            (regions[0], regions[1], regions[2])
+            (regions[0], regions[1], regions[2], regions[3])
         };
         {
             let marked = self.mark_pane.as_ref().map(|p| p.marked());
-            let bg_color = match (marked.map_or(true, |m| m.is_empty()), state.focussed) {
-                (false, FocussedPane::Mark) => Color::LightRed,
-                (false, _) => COLOR_MARKED,
+            let bg_color = match (marked.is_some_and(|m| !m.is_empty()), state.focussed) {
+                (true, FocussedPane::Mark) => Color::LightRed,
+                (true, _) => COLOR_MARKED,
                 (_, _) => Color::White,
             };
-            Header.render(bg_color, header_area, buf);
+            let props = HeaderProps {
+                bg_color,
+                tree,
+                root: state.root,
+                total_bytes: *total_bytes,
+                format: display.byte_format,
+            };
+            Header.render(props, header_area, buf);
         }
-        let (entries_area, help_pane, mark_pane) = {
-            let regions = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Percentage(50), Percentage(50)].as_ref())
-                .split(entries_area);
-            let (left_pane, right_pane) = (regions[0], regions[1]);
-            match (&mut self.help_pane, &mut self.mark_pane) {
-                (Some(ref mut pane), None) => (left_pane, Some((right_pane, pane)), None),
-                (None, Some(ref mut pane)) => (left_pane, None, Some((right_pane, pane))),
-                (Some(ref mut help), Some(ref mut mark)) => {
-                    let regions = Layout::default()
-                        .direction(Direction::Vertical)
-                        .constraints([Percentage(50), Percentage(50)].as_ref())
-                        .split(right_pane);
-                    (
-                        left_pane,
-                        Some((regions[0], help)),
-                        Some((regions[1], mark)),
-                    )
-                }
-                (None, None) => (entries_area, None, None),
-            }
+        let (entries_area, help_pane, details_pane, mark_pane) = {
+            let num_panes = [
+                self.help_pane.is_some(),
+                self.details_pane.is_some(),
+                self.mark_pane.is_some(),
+            ]
+            .iter()
+            .filter(|is_open| **is_open)
+            .count() as u32;
+            let (entries_area, right_panes) = if num_panes == 0 {
+                (entries_area, Vec::new())
+            } else {
+                let regions = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Percentage(50), Percentage(50)].as_ref())
+                    .split(entries_area);
+                let right_panes = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(vec![Ratio(1, num_panes); num_panes as usize])
+                    .split(regions[1]);
+                (regions[0], right_panes)
+            };
+            // The panes on the right are stacked in the order they are cycled through.
+            let mut right_panes = right_panes.into_iter();
+            let mut next_area = || right_panes.next().expect("an area per pane");
+            (
+                entries_area,
+                self.help_pane.as_mut().map(|pane| (next_area(), pane)),
+                self.details_pane.as_mut().map(|pane| (next_area(), pane)),
+                self.mark_pane.as_mut().map(|pane| (next_area(), pane)),
+            )
         };
 
         if let Some((mark_area, pane)) = mark_pane {
             let props = MarkPaneProps {
                 border_style: mark_style,
                 format: display.byte_format,
+                removal: state.removal,
+                skip_confirmation: state.skip_confirmation,
+                deletion: state.deletion.as_ref().map(|job| DeletionStatus {
+                    mode: job.mode,
+                    dry_run: job.dry_run,
+                    reclaimed_bytes: job.reclaimed_bytes(),
+                    total_bytes: job.total_bytes,
+                    entries_removed: job.progress.files_removed(),
+                    errors: job.progress.errors(),
+                }),
             };
             pane.render(props, mark_area, buf);
         }
 
+        if let Some((details_area, pane)) = details_pane {
+            let props = DetailsPaneProps {
+                tree,
+                selected: state.selected,
+                format: display.byte_format,
+                border_style: details_style,
+            };
+            pane.render(props, details_area, buf);
+        }
+
         if let Some((help_area, pane)) = help_pane {
             let props = HelpPaneProps {
                 border_style: help_style,
                 has_focus: matches!(state.focussed, Help),
+                removal: state.removal,
+                skip_confirmation: state.skip_confirmation,
             };
             pane.render(props, help_area, buf);
         }
 
         let marked = self.mark_pane.as_ref().map(|p| p.marked());
+        let entries_area = if let Some(treemap) = self.treemap.as_mut() {
+            let regions = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Percentage(50), Percentage(50)].as_ref())
+                .split(entries_area);
+            let props = TreemapPaneProps {
+                tree,
+                root: state.root,
+                entries: &state.entries,
+                selected: state.selected,
+                marked,
+                format: display.byte_format,
+                border_style: treemap_style,
+            };
+            treemap.render(props, regions[1], buf);
+            regions[0]
+        } else {
+            entries_area
+        };
         let props = EntriesProps {
             tree,
             root: state.root,
@@ -131,9 +195,44 @@ impl MainWindow {
             selected: state.selected,
             border_style: entries_style,
             is_focussed: matches!(state.focussed, Main),
+            baseline: state.baseline.as_ref(),
+            filter: state.filter.as_ref().map(|f| f.pattern.as_str()),
+            sorting: state.sorting,
+            expanded: state.tree_view.then_some(&state.expanded),
         };
         self.entries_pane.render(props, entries_area, buf);
 
+        if let Some(prompt) = &state.prompt {
+            PromptLine.render(prompt, prompt_area, buf);
+        }
+
+        if let (
+            Some(Prompt {
+                kind: PromptKind::ConfirmRemoval(mode),
+                ..
+            }),
+            Some(pane),
+        ) = (&state.prompt, &self.mark_pane)
+        {
+            RemovalConfirmation.render(
+                RemovalConfirmationProps {
+                    mode: *mode,
+                    destination: match mode {
+                        #[cfg(feature = "archive")]
+                        crate::interactive::widgets::MarkMode::Archive { .. } => {
+                            state.archive_path.as_deref()
+                        }
+                        _ => state.move_destination.as_deref(),
+                    },
+                    marked: pane.marked(),
+                    format: display.byte_format,
+                    dry_run: state.removal == RemovalMode::DryRun,
+                },
+                area,
+                buf,
+            );
+        }
+
         Footer.render(
             FooterProps {
                 total_bytes: *total_bytes,
@@ -142,6 +241,10 @@ impl MainWindow {
                 message: state.message.clone(),
                 traversal_start: *start,
                 elapsed: *elapsed,
+                matching_bytes: state
+                    .filter
+                    .as_ref()
+                    .map(|_| state.entries.iter().map(|e| e.data.size).sum()),
             },
             footer_area,
             buf,
@@ -1,5 +1,5 @@
 use crate::interactive::{
-    fit_string_graphemes_with_ellipsis, path_of, widgets::entry_color, CursorDirection,
+    fit_string_graphemes_with_ellipsis, path_of, widgets::entry_color, CursorDirection, RemovalMode,
 };
 use crosstermion::{input::Key, input::Key::*};
 use dua::{
@@ -17,7 +17,7 @@ use tui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, Paragraph, Widget},
+    widgets::{Block, Borders, Gauge, Paragraph, Widget},
 };
 use tui_react::{
     draw_text_nowrap_fn,
@@ -26,10 +26,38 @@ use tui_react::{
 };
 use unicode_segmentation::UnicodeSegmentation;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MarkMode {
     Delete,
+    /// Move entries into another directory.
+    Move,
     #[cfg(feature = "trash-move")]
     Trash,
+    /// Write entries into a compressed archive, and delete them once it was verified if `remove_originals` is set.
+    #[cfg(feature = "archive")]
+    Archive {
+        remove_originals: bool,
+    },
+}
+
+impl MarkMode {
+    /// What happens to entries removed this way, like "delete".
+    pub fn verb(self) -> &'static str {
+        match self {
+            MarkMode::Delete => "delete",
+            MarkMode::Move => "move",
+            #[cfg(feature = "trash-move")]
+            MarkMode::Trash => "trash",
+            #[cfg(feature = "archive")]
+            MarkMode::Archive {
+                remove_originals: false,
+            } => "archive",
+            #[cfg(feature = "archive")]
+            MarkMode::Archive {
+                remove_originals: true,
+            } => "archive and delete",
+        }
+    }
 }
 
 pub type EntryMarkMap = BTreeMap<TreeIndex, EntryMark>;
@@ -53,6 +81,22 @@ pub struct MarkPane {
 pub struct MarkPaneProps {
     pub border_style: Style,
     pub format: ByteFormat,
+    pub removal: RemovalMode,
+    /// If set, marked entries are removed without asking for confirmation.
+    pub skip_confirmation: bool,
+    /// Set while the marked entries are being removed.
+    pub deletion: Option<DeletionStatus>,
+}
+
+/// How far the removal of the marked entries has progressed.
+pub struct DeletionStatus {
+    pub mode: MarkMode,
+    pub dry_run: bool,
+    pub reclaimed_bytes: u128,
+    pub total_bytes: u128,
+    /// Files, symlinks and directories alike.
+    pub entries_removed: u64,
+    pub errors: u64,
 }
 
 impl MarkPane {
@@ -101,18 +145,43 @@ impl MarkPane {
             Some(self)
         }
     }
+    /// Unmark all entries whose index doesn't satisfy `keep`.
+    pub fn retain(mut self, mut keep: impl FnMut(TreeIndex) -> bool) -> Option<Self> {
+        self.marked.retain(|index, _| keep(*index));
+        if let Some(selected) = self.selected.as_mut() {
+            *selected = (*selected).min(self.marked.len().saturating_sub(1));
+        }
+        if self.marked.is_empty() {
+            None
+        } else {
+            Some(self)
+        }
+    }
     pub fn marked(&self) -> &EntryMarkMap {
         &self.marked
     }
     pub fn into_paths(self) -> impl Iterator<Item = PathBuf> {
         self.marked.into_values().map(|v| v.path)
     }
-    pub fn process_events(mut self, key: Key) -> Option<(Self, Option<MarkMode>)> {
+    /// Handle `key`, and return the way to remove all marked entries with if the user asked for it.
+    /// Marked entries can only be removed if `allow_removal` is set.
+    pub fn process_events(
+        mut self,
+        key: Key,
+        allow_removal: bool,
+    ) -> Option<(Self, Option<MarkMode>)> {
         let action = None;
         match key {
-            Ctrl('r') => return Some(self.prepare_deletion(MarkMode::Delete)),
+            Ctrl('r') if allow_removal => return Some(self.prepare_deletion(MarkMode::Delete)),
+            Ctrl('o') if allow_removal => return Some(self.prepare_deletion(MarkMode::Move)),
             #[cfg(feature = "trash-move")]
-            Ctrl('t') => return Some(self.prepare_deletion(MarkMode::Trash)),
+            Ctrl('t') if allow_removal => return Some(self.prepare_deletion(MarkMode::Trash)),
+            #[cfg(feature = "archive")]
+            Ctrl('a') if allow_removal => {
+                return Some(self.prepare_deletion(MarkMode::Archive {
+                    remove_originals: false,
+                }))
+            }
             Char('x') | Char('d') | Char(' ') => {
                 return self.remove_selected().map(|s| (s, action))
             }
@@ -128,58 +197,10 @@ impl MarkPane {
         Some((self, action))
     }
 
-    pub fn iterate_deletable_items(
-        mut self,
-        mut delete_fn: impl FnMut(Self, TreeIndex) -> Result<Self, (Self, usize)>,
-    ) -> Option<Self> {
-        loop {
-            match self.next_entry_for_deletion() {
-                Some(entry_to_delete) => match delete_fn(self, entry_to_delete) {
-                    Ok(pane) => {
-                        self = pane;
-                        match self.delete_entry() {
-                            Some(p) => self = p,
-                            None => return None,
-                        }
-                    }
-                    Err((pane, num_errors)) => {
-                        self = pane;
-                        self.set_error_on_marked_item(num_errors)
-                    }
-                },
-                None => return Some(self),
-            }
-        }
-    }
-
-    fn next_entry_for_deletion(&mut self) -> Option<TreeIndex> {
-        match self.selected.and_then(|selected| {
-            self.tree_index_by_list_position(selected)
-                .and_then(|idx| self.marked.get(&idx).map(|d| (selected, idx, d)))
-        }) {
-            Some((position, selected_index, data)) => match data.num_errors_during_deletion {
-                0 => Some(selected_index),
-                _ => {
-                    self.selected = match position + 1 {
-                        p if p < self.marked.len() => Some(p),
-                        _ => Some(self.marked.len().saturating_sub(1)),
-                    };
-                    self.tree_index_by_list_position(position + 1)
-                }
-            },
-            None => None,
-        }
-    }
-    fn delete_entry(self) -> Option<Self> {
-        self.remove_selected()
-    }
-    fn set_error_on_marked_item(&mut self, num_errors: usize) {
-        if let Some(d) = self
-            .selected
-            .and_then(|s| self.tree_index_by_list_position(s))
-            .and_then(|p| self.marked.get_mut(&p))
-        {
-            d.num_errors_during_deletion = num_errors;
+    /// Record that `num_errors` occurred while removing the entry at `index`.
+    pub fn set_deletion_errors(&mut self, index: TreeIndex, num_errors: usize) {
+        if let Some(entry) = self.marked.get_mut(&index) {
+            entry.num_errors_during_deletion = num_errors;
         }
     }
     fn prepare_deletion(mut self, mark: MarkMode) -> (Self, Option<MarkMode>) {
@@ -214,7 +235,8 @@ impl MarkPane {
             .map(|(k, _)| *k.to_owned())
     }
 
-    fn marked_sorted_by_index(&self) -> Vec<(&TreeIndex, &EntryMark)> {
+    /// Return all marked entries in the order they were marked in.
+    pub fn marked_sorted_by_index(&self) -> Vec<(&TreeIndex, &EntryMark)> {
         self.marked
             .iter()
             .sorted_by_key(|(_, v)| &v.index)
@@ -233,13 +255,16 @@ impl MarkPane {
         let MarkPaneProps {
             border_style,
             format,
+            removal,
+            skip_confirmation,
+            deletion,
         } = props.borrow();
 
         let marked: &_ = &self.marked;
         let title = format!(
             "Marked {} items ({}) ",
             marked.len(),
-            format.display(marked.iter().map(|(_k, v)| v.size).sum::<u128>())
+            format.display(marked.values().map(|v| v.size).sum::<u128>())
         );
         let selected = self.selected;
         let has_focus = self.has_focus;
@@ -332,7 +357,19 @@ impl MarkPane {
         let inner_area = block.inner(area);
         block.render(area, buf);
 
-        let list_area = if self.has_focus {
+        let inner_area = match deletion {
+            Some(deletion) => {
+                let regions = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Max(256), Constraint::Length(1)])
+                    .split(inner_area);
+                render_deletion_gauge(deletion, *format, regions[1], buf);
+                regions[0]
+            }
+            None => inner_area,
+        };
+
+        let list_area = if self.has_focus && *removal != RemovalMode::ReadOnly {
             let (help_line_area, list_area) = {
                 let help_at_bottom =
                     selected.unwrap_or(0) >= inner_area.height.saturating_sub(1) as usize / 2;
@@ -361,6 +398,26 @@ impl MarkPane {
                 sub_modifier: Modifier::empty(),
             };
             Paragraph::new(Text::from(Spans::from(vec![
+                Span::styled(
+                    " Ctrl + o ",
+                    Style {
+                        fg: Color::White.into(),
+                        bg: Color::Black.into(),
+                        ..default_style
+                    },
+                ),
+                Span::styled(" to move, ", default_style),
+                #[cfg(feature = "archive")]
+                Span::styled(
+                    " Ctrl + a ",
+                    Style {
+                        fg: Color::White.into(),
+                        bg: Color::Black.into(),
+                        ..default_style
+                    },
+                ),
+                #[cfg(feature = "archive")]
+                Span::styled(" to archive, ", default_style),
                 #[cfg(feature = "trash-move")]
                 Span::styled(
                     " Ctrl + t ",
@@ -381,7 +438,22 @@ impl MarkPane {
                         ..default_style
                     },
                 ),
-                Span::styled(" to delete without prompt", default_style),
+                Span::styled(
+                    format!(
+                        " to delete{}{}",
+                        if *skip_confirmation {
+                            " without prompt"
+                        } else {
+                            ""
+                        },
+                        if *removal == RemovalMode::DryRun {
+                            " (dry run)"
+                        } else {
+                            ""
+                        }
+                    ),
+                    default_style,
+                ),
             ])))
             .style(default_style)
             .render(help_line_area, buf);
@@ -425,3 +497,56 @@ impl MarkPane {
         }
     }
 }
+
+fn render_deletion_gauge(
+    deletion: &DeletionStatus,
+    format: ByteFormat,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let DeletionStatus {
+        mode,
+        dry_run,
+        reclaimed_bytes,
+        total_bytes,
+        entries_removed,
+        errors,
+    } = deletion;
+    let activity = match mode {
+        MarkMode::Delete => "Deleting",
+        MarkMode::Move => "Moving",
+        #[cfg(feature = "trash-move")]
+        MarkMode::Trash => "Trashing",
+        #[cfg(feature = "archive")]
+        MarkMode::Archive { .. } => "Archiving",
+    };
+    let mut label = format!(
+        "{activity} {} of {}",
+        format.display(*reclaimed_bytes),
+        format.display(*total_bytes)
+    );
+    if *entries_removed != 0 {
+        label.push_str(&format!(", {entries_removed} entries"));
+    }
+    if *errors != 0 {
+        label.push_str(&format!(", {errors} errors"));
+    }
+    if *dry_run {
+        label.push_str(" (dry run)");
+    } else {
+        label.push_str(" - Ctrl + x to cancel");
+    }
+    Gauge::default()
+        .ratio(if *total_bytes == 0 {
+            0.0
+        } else {
+            (*reclaimed_bytes as f64 / *total_bytes as f64).min(1.0)
+        })
+        .label(label)
+        .gauge_style(Style {
+            fg: Color::LightRed.into(),
+            bg: Color::Black.into(),
+            ..Style::default()
+        })
+        .render(area, buf);
+}
@@ -1,16 +1,24 @@
+mod confirm;
+mod details;
 mod entries;
 mod footer;
 mod header;
 mod help;
 mod main;
 mod mark;
+mod prompt;
+mod treemap;
 
+pub use confirm::*;
+pub use details::*;
 pub use entries::*;
 pub use footer::*;
 pub use header::*;
 pub use help::*;
 pub use main::*;
 pub use mark::*;
+pub use prompt::*;
+pub use treemap::*;
 
 use tui::style::Color;
 
@@ -0,0 +1,24 @@
+use crate::interactive::Prompt;
+use std::borrow::Borrow;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Paragraph, Widget},
+};
+
+/// Draws the text typed into a [`Prompt`] on a single line, followed by a cursor.
+pub struct PromptLine;
+
+impl PromptLine {
+    pub fn render(&self, prompt: impl Borrow<Prompt>, area: Rect, buf: &mut Buffer) {
+        let Prompt { kind, input } = prompt.borrow();
+        Paragraph::new(Spans::from(vec![
+            Span::styled(kind.label(), Style::default().add_modifier(Modifier::BOLD)),
+            Span::from(input.as_str()),
+            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+        ]))
+        .render(area, buf);
+    }
+}
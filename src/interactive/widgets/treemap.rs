@@ -0,0 +1,278 @@
+use crate::interactive::{
+    path_of,
+    widgets::{entry_color, EntryMarkMap},
+    EntryDataBundle,
+};
+use dua::{
+    traverse::{Tree, TreeIndex},
+    ByteFormat,
+};
+use petgraph::Direction;
+use std::borrow::Borrow;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Widget},
+};
+
+/// How many levels of directories are drawn within each other at most.
+const MAX_DEPTH: usize = 3;
+/// How many times higher than wide a terminal cell is, to make rectangles look square.
+const CELL_ASPECT_RATIO: f64 = 2.0;
+
+pub struct TreemapPaneProps<'a> {
+    pub tree: &'a Tree,
+    pub root: TreeIndex,
+    /// The entries to draw, of which only the ones directly in `root` are used.
+    pub entries: &'a [EntryDataBundle],
+    pub selected: Option<TreeIndex>,
+    pub marked: Option<&'a EntryMarkMap>,
+    pub format: ByteFormat,
+    pub border_style: Style,
+}
+
+/// A direction to move the selection in the treemap in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreemapDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// The entries of the current directory drawn as rectangles with an area proportional to their size,
+/// with the entries of directories drawn within them.
+#[derive(Default)]
+pub struct TreemapPane {
+    /// The rectangles of the entries directly in the current directory, as drawn last.
+    rects: Vec<(TreeIndex, Rect)>,
+}
+
+impl TreemapPane {
+    /// Return the entry drawn next to `selected` in `direction`, or the first one drawn if `selected` isn't drawn.
+    pub fn neighbour(
+        &self,
+        selected: Option<TreeIndex>,
+        direction: TreemapDirection,
+    ) -> Option<TreeIndex> {
+        let current = match self.rects.iter().find(|(idx, _)| Some(*idx) == selected) {
+            Some((_, rect)) => *rect,
+            None => return self.rects.first().map(|(idx, _)| *idx),
+        };
+        let center = |r: Rect| (r.x * 2 + r.width, r.y * 2 + r.height);
+        let (cx, cy) = center(current);
+        self.rects
+            .iter()
+            .filter_map(|(idx, r)| {
+                // The gap between both rectangles, and how far their centers are apart on the other axis.
+                let (gap, offset) = match direction {
+                    TreemapDirection::Left if r.right() <= current.left() => {
+                        (current.left() - r.right(), center(*r).1.abs_diff(cy))
+                    }
+                    TreemapDirection::Right if r.left() >= current.right() => {
+                        (r.left() - current.right(), center(*r).1.abs_diff(cy))
+                    }
+                    TreemapDirection::Up if r.bottom() <= current.top() => {
+                        (current.top() - r.bottom(), center(*r).0.abs_diff(cx))
+                    }
+                    TreemapDirection::Down if r.top() >= current.bottom() => {
+                        (r.top() - current.bottom(), center(*r).0.abs_diff(cx))
+                    }
+                    _ => return None,
+                };
+                Some(((gap, offset), *idx))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, idx)| idx)
+    }
+
+    pub fn render<'a>(
+        &mut self,
+        props: impl Borrow<TreemapPaneProps<'a>>,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let props = props.borrow();
+        let TreemapPaneProps {
+            tree,
+            root,
+            entries,
+            border_style,
+            ..
+        } = props;
+        let block = Block::default()
+            .title(format!(" Treemap of {} ", path_of(tree, *root).display()))
+            .border_style(*border_style)
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let mut top_level: Vec<_> = entries
+            .iter()
+            .filter(|e| tree.neighbors_directed(e.index, Direction::Incoming).next() == Some(*root))
+            .map(|e| (e.index, e.data.size))
+            .collect();
+        top_level.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        self.render_entries(props, top_level, 0, inner, buf);
+    }
+
+    fn render_entries(
+        &mut self,
+        props: &TreemapPaneProps<'_>,
+        mut entries: Vec<(TreeIndex, u128)>,
+        depth: usize,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        // There is no room for more entries than cells.
+        entries.retain(|(_, size)| *size != 0);
+        entries.truncate(area.area() as usize);
+        let sizes: Vec<_> = entries.iter().map(|(_, size)| *size).collect();
+        if depth == 0 {
+            self.rects.clear();
+        }
+        for ((index, _), rect) in entries.into_iter().zip(squarify(&sizes, area)) {
+            if depth == 0 && rect.area() != 0 {
+                self.rects.push((index, rect));
+            }
+            self.render_entry(props, index, depth, rect, buf);
+        }
+    }
+
+    fn render_entry(
+        &mut self,
+        props: &TreemapPaneProps<'_>,
+        index: TreeIndex,
+        depth: usize,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        if area.area() == 0 {
+            return;
+        }
+        let TreemapPaneProps {
+            tree,
+            selected,
+            marked,
+            format,
+            ..
+        } = props;
+        let entry = &tree[index];
+        let is_dir = entry.entry_type.is_dir();
+        let is_marked = marked.is_some_and(|m| m.contains_key(&index));
+        let mut style = Style {
+            fg: entry_color(Color::Reset.into(), !is_dir, is_marked),
+            ..Style::default()
+        };
+        if *selected == Some(index) {
+            style
+                .add_modifier
+                .insert(Modifier::REVERSED | Modifier::BOLD);
+        }
+
+        if area.width < 3 || area.height < 2 {
+            buf.set_style(area, style);
+            for y in area.top()..area.bottom() {
+                for x in area.left()..area.right() {
+                    buf.get_mut(x, y).set_symbol("░");
+                }
+            }
+            return;
+        }
+        let block = Block::default()
+            .title(format!(
+                "{} {}",
+                entry.name.to_string_lossy(),
+                format.display(entry.size)
+            ))
+            .border_style(style)
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if is_dir && depth + 1 < MAX_DEPTH {
+            let mut children: Vec<_> = tree
+                .neighbors_directed(index, Direction::Outgoing)
+                .map(|idx| (idx, tree[idx].size))
+                .collect();
+            children.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+            self.render_entries(props, children, depth + 1, inner, buf);
+        }
+    }
+}
+
+/// Divide `area` into one rectangle for each of `sizes`, which must be sorted in descending order, so that each
+/// covers a part of `area` proportional to its size and they are as square as possible.
+pub fn squarify(sizes: &[u128], area: Rect) -> Vec<Rect> {
+    let total = sizes.iter().sum::<u128>() as f64;
+    if total == 0.0 || area.area() == 0 {
+        return vec![Rect::default(); sizes.len()];
+    }
+    // Lay out rectangles in a space where cells are square, and stretch them horizontally afterwards.
+    let (mut x, mut y) = (0.0, 0.0);
+    let (mut width, mut height) = (
+        f64::from(area.width) / CELL_ASPECT_RATIO,
+        f64::from(area.height),
+    );
+    let scale = width * height / total;
+    let areas: Vec<f64> = sizes.iter().map(|size| *size as f64 * scale).collect();
+    let to_cells = |x0: f64, y0: f64, x1: f64, y1: f64| {
+        let (x0, x1) = (
+            (x0 * CELL_ASPECT_RATIO).round() as u16,
+            (x1 * CELL_ASPECT_RATIO).round() as u16,
+        );
+        let (y0, y1) = (y0.round() as u16, y1.round() as u16);
+        Rect {
+            x: area.x + x0.min(area.width),
+            y: area.y + y0.min(area.height),
+            width: x1.min(area.width).saturating_sub(x0),
+            height: y1.min(area.height).saturating_sub(y0),
+        }
+    };
+
+    let mut rects = Vec::with_capacity(sizes.len());
+    let mut start = 0;
+    while start < areas.len() {
+        let side = width.min(height);
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst_aspect_ratio(&areas[start..=end], side)
+                <= worst_aspect_ratio(&areas[start..end], side)
+        {
+            end += 1;
+        }
+        let row = &areas[start..end];
+        let thickness = row.iter().sum::<f64>() / side;
+        let mut offset = 0.0;
+        for length in row
+            .iter()
+            .map(|a| if thickness > 0.0 { a / thickness } else { 0.0 })
+        {
+            rects.push(if width >= height {
+                to_cells(x, y + offset, x + thickness, y + offset + length)
+            } else {
+                to_cells(x + offset, y, x + offset + length, y + thickness)
+            });
+            offset += length;
+        }
+        if width >= height {
+            x += thickness;
+            width -= thickness;
+        } else {
+            y += thickness;
+            height -= thickness;
+        }
+        start = end;
+    }
+    rects
+}
+
+/// The largest ratio between the long and the short side of `areas` laid out in a row along `side`.
+fn worst_aspect_ratio(areas: &[f64], side: f64) -> f64 {
+    let sum: f64 = areas.iter().sum();
+    let (min, max) = areas.iter().fold((f64::MAX, 0.0f64), |(min, max), a| {
+        (min.min(*a), max.max(*a))
+    });
+    let (side, sum) = (side * side, sum * sum);
+    (side * max / sum).max(sum / (side * min))
+}
@@ -4,12 +4,31 @@
 extern crate jwalk;
 
 mod aggregate;
+#[cfg(feature = "archive")]
+mod archive;
 mod common;
 mod crossdev;
+mod delete;
+mod diff;
+mod ignorefilter;
 mod inodefilter;
+mod ncdu;
+mod relocate;
+mod snapshot;
+#[cfg(test)]
+mod testing;
 
 pub mod traverse;
 
-pub use aggregate::aggregate;
+pub use aggregate::{
+    aggregate, aggregate_tree, top, OutputFormat, Statistics, TreeOptions, TreeStyle,
+};
+#[cfg(feature = "archive")]
+pub use archive::{verify_archive, ArchiveFormat, ArchiveSummary, ArchiveWriter};
 pub use common::*;
+pub use delete::{delete_recursively, DeletionProgress};
+pub use diff::{
+    diff, directory_changes, format_delta, match_trees, matching_node, MatchedEntry, SizeChange,
+};
 pub(crate) use inodefilter::InodeFilter;
+pub use relocate::move_recursively;
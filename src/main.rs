@@ -1,5 +1,5 @@
 #![forbid(unsafe_code)]
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use dua::TraversalSorting;
 use std::{fs, io, io::Write, path::PathBuf, process};
@@ -8,6 +8,8 @@ mod crossdev;
 #[cfg(any(feature = "tui-unix", feature = "tui-crossplatform"))]
 mod interactive;
 mod options;
+#[cfg(all(test, any(feature = "tui-unix", feature = "tui-crossplatform")))]
+mod testing;
 
 fn stderr_if_tty() -> Option<io::Stderr> {
     if atty::is(atty::Stream::Stderr) {
@@ -29,12 +31,25 @@ fn main() -> Result<()> {
         sorting: TraversalSorting::None,
         cross_filesystems: !opt.stay_on_filesystem,
         ignore_dirs: opt.ignore_dirs,
+        exclude: dua::ExcludeFilter::new(&opt.exclude)
+            .with_context(|| "Invalid --exclude pattern")?,
+        respect_ignore_files: opt.respect_ignore_files,
     };
     let res = match opt.command {
         #[cfg(any(feature = "tui-unix", feature = "tui-crossplatform"))]
-        Some(Interactive { input }) => {
-            use crate::interactive::{Interaction, TerminalApp};
-            use anyhow::{anyhow, Context};
+        Some(Interactive {
+            input,
+            save,
+            load,
+            compare,
+            import_ncdu,
+            export_ncdu,
+            read_only,
+            dry_run,
+            no_confirm,
+        }) => {
+            use crate::interactive::{Interaction, RemovalMode, TerminalApp};
+            use anyhow::anyhow;
             use crosstermion::terminal::{tui::new_terminal, AlternateRawScreen};
 
             let no_tty_msg = "Interactive mode requires a connected terminal";
@@ -42,22 +57,90 @@ fn main() -> Result<()> {
                 return Err(anyhow!(no_tty_msg));
             }
 
+            // Create files right away to fail early, and not after a possibly lengthy traversal.
+            let create_file = |path: &PathBuf| {
+                fs::File::create(path)
+                    .with_context(|| format!("Could not create file at '{}'", path.display()))
+            };
+            let snapshot_file = save.as_ref().map(create_file).transpose()?;
+            let ncdu_export_file = export_ncdu.as_ref().map(create_file).transpose()?;
+            let traversal = match (load, import_ncdu) {
+                (Some(path), _) => Some(load_snapshot(path)?),
+                (None, Some(path)) => Some(
+                    fs::File::open(&path)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|file| {
+                            dua::traverse::Traversal::from_ncdu_export(file, &walk_options)
+                        })
+                        .with_context(|| {
+                            format!("Could not import ncdu export from '{}'", path.display())
+                        })?,
+                ),
+                (None, None) => None,
+            };
+            let baseline = compare.map(load_snapshot).transpose()?;
+
             let mut terminal = new_terminal(
                 AlternateRawScreen::try_from(io::stderr()).with_context(|| no_tty_msg)?,
             )
             .with_context(|| "Could not instantiate terminal")?;
-            let res = TerminalApp::initialize(
-                &mut terminal,
-                walk_options,
-                paths_from(input, !opt.stay_on_filesystem)?,
-                Interaction::Full,
-            )?
-            .map(|(keys_rx, mut app)| {
-                let res = app.process_events(&mut terminal, keys_rx.into_iter());
+            let app = match traversal {
+                Some(traversal) => Some(TerminalApp::initialize_with_traversal(
+                    &mut terminal,
+                    walk_options,
+                    traversal,
+                    Interaction::Full,
+                )?),
+                None => TerminalApp::initialize(
+                    &mut terminal,
+                    walk_options,
+                    paths_from(input, !opt.stay_on_filesystem)?,
+                    Interaction::Full,
+                )?,
+            };
+            let (snapshot_file, ncdu_export_file) = match &app {
+                Some(_) => (snapshot_file, ncdu_export_file),
+                None => {
+                    // The traversal was aborted, which leaves nothing to write into the files created for it.
+                    drop((snapshot_file, ncdu_export_file));
+                    for path in save.iter().chain(export_ncdu.iter()) {
+                        fs::remove_file(path).ok();
+                    }
+                    (None, None)
+                }
+            };
+            if let (Some((_, app)), Some(file)) = (&app, snapshot_file) {
+                app.traversal
+                    .save_snapshot(file)
+                    .with_context(|| "Could not write snapshot")?;
+            }
+            let apparent_size = opt.apparent_size;
+            let res = app.map(|(keys_rx, mut app)| {
+                app.state.baseline = baseline;
+                app.state.removal = if read_only || app.state.is_loaded {
+                    RemovalMode::ReadOnly
+                } else if dry_run {
+                    RemovalMode::DryRun
+                } else {
+                    RemovalMode::Enabled
+                };
+                app.state.skip_confirmation = no_confirm;
+                let res = app
+                    .process_events(&mut terminal, keys_rx.into_iter())
+                    .and_then(|r| {
+                        // Written last to include everything that was removed or rescanned in the meantime.
+                        if let Some(file) = ncdu_export_file {
+                            app.traversal
+                                .export_ncdu(file, apparent_size)
+                                .with_context(|| "Could not write ncdu export")?;
+                        }
+                        Ok(r)
+                    });
 
                 let res = res.map(|r| {
                     (
                         r,
+                        std::mem::take(&mut app.state.dry_run_removals),
                         app.window
                             .mark_pane
                             .take()
@@ -76,7 +159,18 @@ fn main() -> Result<()> {
             // Exit 'quickly' to avoid having to not have to deal with slightly different types in the other match branches
             std::process::exit(
                 res.transpose()?
-                    .map(|(walk_result, paths)| {
+                    .map(|(walk_result, dry_run_removals, paths)| {
+                        for (mode, path, destination) in dry_run_removals {
+                            match destination {
+                                Some(destination) => println!(
+                                    "would {} {} into {}",
+                                    mode.verb(),
+                                    path.display(),
+                                    destination.display()
+                                ),
+                                None => println!("would {} {}", mode.verb(), path.display()),
+                            }
+                        }
                         if let Some(paths) = paths {
                             for path in paths {
                                 println!("{}", path.display())
@@ -91,16 +185,69 @@ fn main() -> Result<()> {
             input,
             no_total,
             no_sort,
+            depth,
+            flat,
             statistics,
         }) => {
             let stdout = io::stdout();
             let stdout_locked = stdout.lock();
-            let (res, stats) = dua::aggregate(
+            let paths = paths_from(input, !opt.stay_on_filesystem)?;
+            let (res, stats) = match depth {
+                Some(max_depth) => dua::aggregate_tree(
+                    stdout_locked,
+                    stderr_if_tty(),
+                    walk_options,
+                    opt.output.into(),
+                    dua::TreeOptions {
+                        max_depth,
+                        style: if flat {
+                            dua::TreeStyle::Flat
+                        } else {
+                            dua::TreeStyle::Indented
+                        },
+                        compute_total: !no_total,
+                        sort_by_size_in_bytes: !no_sort,
+                    },
+                    paths,
+                )?,
+                None => dua::aggregate(
+                    stdout_locked,
+                    stderr_if_tty(),
+                    walk_options,
+                    opt.output.into(),
+                    !no_total,
+                    !no_sort,
+                    paths,
+                )?,
+            };
+            if statistics {
+                writeln!(io::stderr(), "{:?}", stats).ok();
+            }
+            res
+        }
+        Some(Diff { old, new }) => {
+            dua::diff(
+                io::stdout().lock(),
+                opt.output.into(),
+                walk_options.byte_format,
+                &load_snapshot(old)?,
+                &load_snapshot(new)?,
+            )?;
+            dua::WalkResult::default()
+        }
+        Some(Top {
+            input,
+            num_files,
+            statistics,
+        }) => {
+            let stdout = io::stdout();
+            let stdout_locked = stdout.lock();
+            let (res, stats) = dua::top(
                 stdout_locked,
                 stderr_if_tty(),
                 walk_options,
-                !no_total,
-                !no_sort,
+                opt.output.into(),
+                num_files,
                 paths_from(input, !opt.stay_on_filesystem)?,
             )?;
             if statistics {
@@ -115,6 +262,7 @@ fn main() -> Result<()> {
                 stdout_locked,
                 stderr_if_tty(),
                 walk_options,
+                opt.output.into(),
                 true,
                 true,
                 paths_from(opt.input, !opt.stay_on_filesystem)?,
@@ -126,6 +274,13 @@ fn main() -> Result<()> {
     process::exit(res.to_exit_code());
 }
 
+fn load_snapshot(path: PathBuf) -> Result<dua::traverse::Traversal> {
+    fs::File::open(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(dua::traverse::Traversal::from_snapshot)
+        .with_context(|| format!("Could not load snapshot from '{}'", path.display()))
+}
+
 fn paths_from(paths: Vec<PathBuf>, cross_filesystems: bool) -> Result<Vec<PathBuf>, io::Error> {
     let device_id = std::env::current_dir()
         .ok()
@@ -0,0 +1,328 @@
+//! Reading and writing the JSON export format of [ncdu](https://dev.yorhel.nl/ncdu/jsonfmt).
+use crate::traverse::{EntryData, EntryType, Traversal, TreeIndex};
+use crate::{get_entry_or_panic, InodeFilter, WalkOptions};
+use anyhow::{bail, Context, Result};
+use petgraph::Direction;
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufReader, BufWriter, Read, Write},
+    time::{Duration, Instant, SystemTime},
+};
+
+const MAJOR_VERSION: u64 = 1;
+const MINOR_VERSION: u64 = 2;
+
+impl Traversal {
+    /// Read a traversal from an ncdu JSON export, as written by `ncdu -o`.
+    ///
+    /// The apparent size of entries is used if `walk_options.apparent_size` is set, their disk usage otherwise.
+    /// Unlike during a filesystem walk, the size of directories themselves is included as well, to match the
+    /// totals shown by ncdu.
+    pub fn from_ncdu_export(input: impl Read, walk_options: &WalkOptions) -> Result<Traversal> {
+        let start = Instant::now();
+        let export: Value = serde_json::from_reader(BufReader::new(input))?;
+        let (major_version, root) = match export.as_array().map(Vec::as_slice) {
+            Some([major_version, _minor_version, _metadata, root, ..]) => (major_version, root),
+            _ => bail!("Not an ncdu JSON export"),
+        };
+        if major_version.as_u64() != Some(MAJOR_VERSION) {
+            bail!("Unsupported ncdu export version {major_version}, expected {MAJOR_VERSION}")
+        }
+
+        let mut t = Traversal::empty(start);
+        let mut importer = Importer {
+            size_field: if walk_options.apparent_size {
+                "asize"
+            } else {
+                "dsize"
+            },
+            count_hard_links: walk_options.count_hard_links,
+            inodes: InodeFilter::default(),
+        };
+        let root_index = t.root_index;
+        let top_level_idx = importer.add_entry(&mut t, root_index, root, 0)?;
+        let top_level = &t.tree[top_level_idx];
+        let (size, entry_count, file_count, mtime) = (
+            top_level.size,
+            top_level.entry_count + 1,
+            top_level.file_count + u64::from(!top_level.entry_type.is_dir()),
+            top_level.mtime,
+        );
+        let root = &mut t.tree[root_index];
+        root.size = size;
+        root.entry_count = entry_count;
+        root.file_count = file_count;
+        root.mtime = mtime;
+        t.total_bytes = Some(size);
+        t.elapsed = Some(start.elapsed());
+        Ok(t)
+    }
+
+    /// Write this traversal as an ncdu JSON export, to be read with `ncdu -f`.
+    ///
+    /// As only one kind of size is known per entry, it is written as apparent size if `apparent_size` is set,
+    /// or as disk usage otherwise.
+    /// Multiple top-level entries are placed into a directory named after the current working directory,
+    /// as ncdu expects a single one.
+    pub fn export_ncdu(&self, out: impl Write, apparent_size: bool) -> io::Result<()> {
+        let mut out = BufWriter::new(out);
+        let size_field = if apparent_size { "asize" } else { "dsize" };
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        write!(
+            out,
+            "[{MAJOR_VERSION},{MINOR_VERSION},{},",
+            json!({
+                "progname": env!("CARGO_PKG_NAME"),
+                "progver": env!("CARGO_PKG_VERSION"),
+                "timestamp": timestamp,
+            })
+        )?;
+
+        let top_level: Vec<_> = self
+            .tree
+            .neighbors_directed(self.root_index, Direction::Outgoing)
+            .collect();
+        match top_level.as_slice() {
+            [top_level_idx] => self.write_ncdu_entry(&mut out, *top_level_idx, size_field)?,
+            _ => {
+                let cwd = std::env::current_dir().unwrap_or_else(|_| ".".into());
+                write!(out, "[{}", json!({ "name": cwd.to_string_lossy() }))?;
+                for idx in top_level.into_iter().rev() {
+                    out.write_all(b",")?;
+                    self.write_ncdu_entry(&mut out, idx, size_field)?;
+                }
+                out.write_all(b"]")?;
+            }
+        }
+        out.write_all(b"]\n")?;
+        out.flush()
+    }
+
+    fn write_ncdu_entry(
+        &self,
+        out: &mut impl Write,
+        node_idx: TreeIndex,
+        size_field: &str,
+    ) -> io::Result<()> {
+        let entry = get_entry_or_panic(&self.tree, node_idx);
+        let mut children: Vec<_> = self
+            .tree
+            .neighbors_directed(node_idx, Direction::Outgoing)
+            .collect();
+        // Children are returned newest first.
+        children.reverse();
+        let children_size: u128 = children
+            .iter()
+            .map(|&idx| get_entry_or_panic(&self.tree, idx).size)
+            .sum();
+
+        let mut info = json!({
+            "name": entry.name.to_string_lossy(),
+            size_field: entry.size.saturating_sub(children_size),
+        });
+        // Directories carry the most recent modification time of their children, which is close enough.
+        if let Some(mtime) = entry
+            .mtime
+            .and_then(|mtime| mtime.duration_since(SystemTime::UNIX_EPOCH).ok())
+        {
+            info["mtime"] = mtime.as_secs().into();
+        }
+        if entry.metadata_io_error {
+            info["read_error"] = true.into();
+        }
+        if matches!(entry.entry_type, EntryType::Symlink | EntryType::Other) {
+            info["notreg"] = true.into();
+        }
+        if children.is_empty() && !entry.entry_type.is_dir() {
+            return write!(out, "{info}");
+        }
+        write!(out, "[{info}")?;
+        for child_idx in children {
+            out.write_all(b",")?;
+            self.write_ncdu_entry(out, child_idx, size_field)?;
+        }
+        out.write_all(b"]")
+    }
+}
+
+struct Importer {
+    size_field: &'static str,
+    count_hard_links: bool,
+    inodes: InodeFilter,
+}
+
+impl Importer {
+    /// Add the file or directory `value` below `parent_idx`, along with all of its children, and return its index.
+    fn add_entry(
+        &mut self,
+        t: &mut Traversal,
+        parent_idx: TreeIndex,
+        value: &Value,
+        parent_device_id: u64,
+    ) -> Result<TreeIndex> {
+        let (info, children, is_dir) = match value {
+            Value::Array(items) => match items.split_first() {
+                Some((info, children)) => (info, children, true),
+                None => bail!("Directory without information in ncdu export"),
+            },
+            info => (info, &[][..], false),
+        };
+        let name = info
+            .get("name")
+            .and_then(Value::as_str)
+            .with_context(|| format!("Entry without name in ncdu export: {info}"))?;
+        let field = |name: &str| info.get(name).and_then(Value::as_u64);
+        let device_id = field("dev").unwrap_or(parent_device_id);
+
+        let mut data = EntryData {
+            name: name.into(),
+            size: field(self.size_field).unwrap_or(0) as u128,
+            mtime: field("mtime").map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            // ncdu doesn't tell symlinks apart from other kinds of non-regular files.
+            entry_type: if is_dir {
+                EntryType::Directory
+            } else if info.get("notreg").and_then(Value::as_bool) == Some(true) {
+                EntryType::Other
+            } else {
+                EntryType::File
+            },
+            ..Default::default()
+        };
+        let is_hard_link = info.get("hlnkc").and_then(Value::as_bool) == Some(true);
+        if is_hard_link && !self.count_hard_links {
+            // Older versions of ncdu don't write the amount of links, so count the inode only once.
+            let num_links = field("nlink").unwrap_or(u64::MAX);
+            if let Some(inode) = field("ino") {
+                if !self.inodes.add_dev_inode((device_id, inode), num_links) {
+                    data.size = 0;
+                }
+            }
+        }
+        if info.get("read_error").and_then(Value::as_bool) == Some(true) {
+            data.metadata_io_error = true;
+            t.io_errors += 1;
+        }
+
+        t.entries_traversed += 1;
+        let node_idx = t.tree.add_node(data);
+        t.tree.add_edge(parent_idx, node_idx, ());
+
+        for child in children {
+            let child_idx = self.add_entry(t, node_idx, child, device_id)?;
+            let child = &t.tree[child_idx];
+            let (size, entry_count, file_count, mtime) = (
+                child.size,
+                child.entry_count,
+                child.file_count + u64::from(!child.entry_type.is_dir()),
+                child.mtime,
+            );
+            let entry = &mut t.tree[node_idx];
+            entry.size += size;
+            entry.entry_count += entry_count + 1;
+            entry.file_count += file_count;
+            entry.mtime = entry.mtime.max(mtime);
+        }
+        Ok(node_idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ByteFormat, TraversalSorting};
+
+    fn walk_options() -> WalkOptions {
+        WalkOptions {
+            threads: 1,
+            byte_format: ByteFormat::Metric,
+            apparent_size: true,
+            count_hard_links: false,
+            sorting: TraversalSorting::AlphabeticalByFileName,
+            cross_filesystems: true,
+            ignore_dirs: Vec::new(),
+            exclude: Default::default(),
+            respect_ignore_files: false,
+        }
+    }
+
+    fn names_and_sizes(t: &Traversal, idx: TreeIndex) -> Vec<(String, u128)> {
+        let mut out: Vec<_> = t
+            .tree
+            .neighbors_directed(idx, Direction::Outgoing)
+            .map(|idx| {
+                let entry = get_entry_or_panic(&t.tree, idx);
+                (entry.name.to_string_lossy().into_owned(), entry.size)
+            })
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn import_counts_hard_links_once_and_read_errors() -> Result<()> {
+        let export = r#"[1, 2, {"progname": "ncdu", "progver": "1.18", "timestamp": 1},
+            [{"name": "/srv", "asize": 10, "dsize": 4096, "dev": 1},
+                {"name": "a", "asize": 100, "dsize": 4096, "ino": 5, "hlnkc": true, "nlink": 2},
+                [{"name": "sub", "asize": 10, "read_error": true},
+                    {"name": "b", "asize": 100, "ino": 5, "hlnkc": true, "nlink": 2},
+                    {"name": "c", "asize": 1}]]]"#;
+
+        let t = Traversal::from_ncdu_export(export.as_bytes(), &walk_options())?;
+        assert_eq!(names_and_sizes(&t, t.root_index), [("/srv".into(), 121)]);
+        assert_eq!(t.total_bytes, Some(121));
+        assert_eq!(t.entries_traversed, 5);
+        assert_eq!(t.io_errors, 1);
+
+        let t = Traversal::from_ncdu_export(
+            export.as_bytes(),
+            &WalkOptions {
+                count_hard_links: true,
+                apparent_size: false,
+                ..walk_options()
+            },
+        )?;
+        assert_eq!(t.total_bytes, Some(4096 * 2), "missing sizes are zero");
+
+        assert!(Traversal::from_ncdu_export(
+            &b"[2, 0, {}, {\"name\": \"/\"}]"[..],
+            &walk_options()
+        )
+        .is_err());
+        assert!(Traversal::from_ncdu_export(&b"{}"[..], &walk_options()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn export_can_be_imported_with_the_same_tree() -> Result<()> {
+        let traversal = Traversal::from_walk(
+            walk_options(),
+            vec!["tests/fixtures/sample-02".into()],
+            |_| Ok(false),
+        )?
+        .expect("not aborted");
+        let mut export = Vec::new();
+        traversal.export_ncdu(&mut export, true)?;
+        let imported = Traversal::from_ncdu_export(export.as_slice(), &walk_options())?;
+        let top_level_idx = |t: &Traversal| {
+            t.tree
+                .neighbors_directed(t.root_index, Direction::Outgoing)
+                .next()
+                .expect("one")
+        };
+
+        assert_eq!(imported.total_bytes, Some(1540));
+        assert_eq!(
+            imported.tree[top_level_idx(&imported)].entry_type,
+            EntryType::Directory
+        );
+        assert_eq!(imported.entries_traversed, traversal.entries_traversed);
+        assert_eq!(
+            names_and_sizes(&imported, top_level_idx(&imported)),
+            names_and_sizes(&traversal, top_level_idx(&traversal))
+        );
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use dua::ByteFormat as LibraryByteFormat;
+use dua::OutputFormat as LibraryOutputFormat;
 use std::path::PathBuf;
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy, clap::ValueEnum)]
@@ -26,6 +27,21 @@ impl From<ByteFormat> for LibraryByteFormat {
     }
 }
 
+#[derive(PartialEq, Eq, Debug, Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+impl From<OutputFormat> for LibraryOutputFormat {
+    fn from(input: OutputFormat) -> Self {
+        match input {
+            OutputFormat::Human => LibraryOutputFormat::Human,
+            OutputFormat::Json => LibraryOutputFormat::Json,
+        }
+    }
+}
+
 fn dft_format() -> ByteFormat {
     if cfg!(target_vendor = "apple") {
         ByteFormat::Metric
@@ -57,6 +73,12 @@ pub struct Args {
     )]
     pub format: ByteFormat,
 
+    /// The format in which results are written to standard output when aggregating.
+    ///
+    /// 'json' writes exact byte counts, IO error counts and statistics for processing by other programs.
+    #[clap(short = 'o', long, value_enum, default_value_t = OutputFormat::Human, ignore_case = true)]
+    pub output: OutputFormat,
+
     /// Display apparent size instead of disk usage.
     #[clap(short = 'A', long)]
     pub apparent_size: bool,
@@ -76,6 +98,20 @@ pub struct Args {
     #[cfg_attr(target_os = "linux", clap(default_values = &["/proc", "/dev", "/sys", "/run"]))]
     pub ignore_dirs: Vec<PathBuf>,
 
+    /// One or more glob patterns of files and directories to skip during the traversal, like '*.iso' or '**/node_modules'.
+    ///
+    /// Patterns without a '/' match the name of entries at any depth, others match their entire path.
+    /// Excluded directories are not entered at all. Input paths are never excluded.
+    #[clap(long = "exclude", short = 'e', value_parser)]
+    pub exclude: Vec<String>,
+
+    /// If set, skip files and directories ignored by '.gitignore' and '.ignore' files or the global git excludes.
+    ///
+    /// Ignore files in parent directories of input paths apply up to the root of their git repository.
+    /// Ignored directories are not entered at all. Input paths are never ignored.
+    #[clap(long)]
+    pub respect_ignore_files: bool,
+
     /// One or more input files or directories. If unset, we will use all entries in the current working directory.
     #[clap(value_parser)]
     pub input: Vec<PathBuf>,
@@ -87,6 +123,38 @@ pub enum Command {
     #[cfg(any(feature = "tui-unix", feature = "tui-crossplatform"))]
     #[clap(name = "interactive", visible_alias = "i")]
     Interactive {
+        /// Write a snapshot of the traversal to the given file once it is done, to open it later with '--load'.
+        #[clap(long, value_name = "FILE")]
+        save: Option<PathBuf>,
+        /// Open a snapshot written with '--save' instead of traversing the filesystem.
+        ///
+        /// Its entries can't be removed or rescanned, as they may not exist on this machine.
+        #[clap(long, value_name = "FILE", conflicts_with = "input")]
+        load: Option<PathBuf>,
+        /// Open an ncdu JSON export, as written by 'ncdu -o', instead of traversing the filesystem.
+        ///
+        /// Like with '--load', its entries can't be removed or rescanned.
+        #[clap(long, value_name = "FILE", conflicts_with_all = ["input", "load"])]
+        import_ncdu: Option<PathBuf>,
+        /// Write the traversal as ncdu JSON export to the given file when the program exits, to be opened with 'ncdu -f'.
+        ///
+        /// Entries removed, moved or rescanned in the meantime are exported as they are then.
+        #[clap(long, value_name = "FILE")]
+        export_ncdu: Option<PathBuf>,
+        /// Compare with a snapshot written with '--save', and show how much each entry grew or shrunk since.
+        #[clap(long, value_name = "FILE")]
+        compare: Option<PathBuf>,
+        /// Don't offer to delete or trash marked entries. Marked entries are still printed on exit.
+        #[clap(long, conflicts_with = "dry_run")]
+        read_only: bool,
+        /// Pretend to delete or trash marked entries without touching the disk, and print what would have been
+        /// removed on exit.
+        #[clap(long)]
+        dry_run: bool,
+        /// Delete or trash marked entries right away, without showing what is about to be removed and asking for
+        /// confirmation first. Archiving still asks whether the originals should be deleted.
+        #[clap(long)]
+        no_confirm: bool,
         /// One or more input files or directories. If unset, we will use all entries in the current working directory.
         #[clap(value_parser)]
         input: Vec<PathBuf>,
@@ -104,6 +172,36 @@ pub enum Command {
         /// If set, no total column will be computed for multiple inputs
         #[clap(long)]
         no_total: bool,
+        /// If set, also print the size of all directories up to the given depth below each input path, like `du --max-depth`.
+        /// A depth of 0 prints only the input paths.
+        #[clap(short = 'd', long)]
+        depth: Option<usize>,
+        /// If set, directories below the input paths are printed with their full path, after their children.
+        /// Otherwise their names are indented below their parent.
+        #[clap(long, requires = "depth")]
+        flat: bool,
+        /// One or more input files or directories. If unset, we will use all entries in the current working directory.
+        #[clap(value_parser)]
+        input: Vec<PathBuf>,
+    },
+    /// Compare two snapshots written with 'interactive --save' and list the directories that grew or shrunk,
+    /// sorted by the absolute amount of change, descending
+    #[clap(name = "diff")]
+    Diff {
+        /// The snapshot of the earlier traversal
+        old: PathBuf,
+        /// The snapshot of the later traversal
+        new: PathBuf,
+    },
+    /// List the largest files found in one or more directories, sorted by their size in bytes, ascending
+    #[clap(name = "top")]
+    Top {
+        /// The amount of files to list
+        #[clap(short = 'n', long, default_value_t = 20)]
+        num_files: usize,
+        /// If set, print additional statistics about the file traversal to stderr
+        #[clap(long = "stats")]
+        statistics: bool,
         /// One or more input files or directories. If unset, we will use all entries in the current working directory.
         #[clap(value_parser)]
         input: Vec<PathBuf>,
@@ -0,0 +1,274 @@
+//! Moving files and directories to another directory, possibly on another filesystem.
+use crate::{crossdev, delete_recursively, DeletionProgress};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Move `path` and everything below it into the directory `destination`, keeping its name, and record what was
+/// removed from its original location in `progress`.
+///
+/// Within the same filesystem, `path` is renamed, which removes no files and leaves `progress` untouched. Otherwise, it's copied first and removed using `threads` once
+/// the copy is complete, without following symlinks. Nothing is removed if anything couldn't be copied, and the
+/// partial copy is removed again. Existing entries at the destination are never overwritten.
+/// If `progress` is cancelled while copying, the partial copy is removed again and `Ok(())` is returned, even
+/// though `path` still exists.
+/// Returns the amount of entries that couldn't be moved as error.
+pub fn move_recursively(
+    path: &Path,
+    destination: &Path,
+    threads: usize,
+    progress: &Arc<DeletionProgress>,
+) -> Result<(), usize> {
+    let (meta, target) = match prepare(path, destination) {
+        Ok(res) => res,
+        Err(kind) => {
+            progress.record_error(kind);
+            return Err(1);
+        }
+    };
+    let is_same_device = crossdev::init(destination)
+        .map(|device_id| crossdev::is_same_device(device_id, &meta))
+        .unwrap_or(false);
+    if is_same_device {
+        match fs::rename(path, &target) {
+            // Nothing is removed by renaming, so only failures are recorded.
+            Ok(()) => return Ok(()),
+            // The same filesystem can be mounted in multiple places, like with bind mounts, which can't be renamed across.
+            Err(err) if crosses_devices(&err) => {}
+            Err(err) => {
+                progress.record_error(err.kind());
+                return Err(1);
+            }
+        }
+    }
+    copy_then_delete(path, &target, threads, progress)
+}
+
+/// Copy `path` to `target` and remove `path` using `threads` afterwards, or remove the partial copy if it couldn't
+/// be completed.
+fn copy_then_delete(
+    path: &Path,
+    target: &Path,
+    threads: usize,
+    progress: &Arc<DeletionProgress>,
+) -> Result<(), usize> {
+    let num_errors = copy_recursively(path, target, progress);
+    if progress.is_cancelled() || num_errors != 0 {
+        delete_recursively(target, threads, &Arc::new(DeletionProgress::default())).ok();
+    }
+    if progress.is_cancelled() {
+        return Ok(());
+    }
+    if num_errors != 0 {
+        return Err(num_errors);
+    }
+    delete_recursively(path, threads, progress)
+}
+
+/// Return `true` if `err` is caused by renaming an entry into another filesystem.
+fn crosses_devices(err: &io::Error) -> bool {
+    // `io::ErrorKind::CrossesDevices` is too recent to be used, so it's `ERROR_NOT_SAME_DEVICE` or `EXDEV`.
+    let code = if cfg!(windows) { 17 } else { 18 };
+    err.raw_os_error() == Some(code)
+}
+
+/// Return the metadata of `path` and where it should be moved to, if it can be moved into `destination`.
+fn prepare(path: &Path, destination: &Path) -> Result<(fs::Metadata, PathBuf), io::ErrorKind> {
+    let meta = path.symlink_metadata().map_err(|e| e.kind())?;
+    let name = path.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+    let target = destination.join(name);
+    if target.symlink_metadata().is_ok() {
+        return Err(io::ErrorKind::AlreadyExists);
+    }
+    let destination = destination.canonicalize().map_err(|e| e.kind())?;
+    if meta.is_dir() && destination.starts_with(path.canonicalize().map_err(|e| e.kind())?) {
+        // A directory can't be moved into itself.
+        return Err(io::ErrorKind::InvalidInput);
+    }
+    Ok((meta, target))
+}
+
+/// Copy `path` with all of its contents to `target`, and return the amount of entries that couldn't be copied.
+/// The permissions and modification times of directories are retained, as far as possible.
+fn copy_recursively(path: &Path, target: &Path, progress: &DeletionProgress) -> usize {
+    let mut num_errors = 0;
+    let mut directories = Vec::new();
+    let walk = jwalk::WalkDir::new(path)
+        .follow_links(false)
+        .skip_hidden(false)
+        .sort(false)
+        .parallelism(jwalk::Parallelism::Serial);
+    // Directories are returned before their contents.
+    for entry in walk {
+        if progress.is_cancelled() {
+            break;
+        }
+        let result = entry.map_err(io::Error::from).and_then(|entry| {
+            let src = entry.path();
+            let dst = target.join(
+                src.strip_prefix(path)
+                    .expect("entries are below the walked path"),
+            );
+            if entry.file_type.is_dir() {
+                fs::create_dir(&dst)?;
+                directories.push((dst, entry.metadata()?));
+                Ok(())
+            } else if entry.file_type.is_symlink() {
+                copy_symlink(&src, &dst)
+            } else {
+                fs::copy(src, dst).map(|_| ())
+            }
+        });
+        if result.is_err() {
+            num_errors += 1;
+        }
+    }
+    // Directories are only completed once nothing is added to them anymore, innermost first in case they are read-only.
+    for (dst, meta) in directories.into_iter().rev() {
+        if let Ok(mtime) = meta.modified() {
+            fs::File::open(&dst)
+                .and_then(|dir| dir.set_modified(mtime))
+                .ok();
+        }
+        fs::set_permissions(&dst, meta.permissions()).ok();
+    }
+    num_errors
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn entries_are_moved_into_the_destination_without_overwriting() -> io::Result<()> {
+        let tmp = TempDir::new("dua-move")?;
+        let root = tmp.path();
+        let source = root.join("source");
+        let destination = root.join("destination");
+        fs::create_dir_all(source.join("dir/sub"))?;
+        fs::create_dir_all(&destination)?;
+        fs::write(source.join("dir/sub/file"), [0; 100])?;
+        fs::write(source.join("file"), [0; 10])?;
+
+        let progress = Arc::new(DeletionProgress::default());
+        assert_eq!(
+            move_recursively(&source.join("dir"), &destination, 1, &progress),
+            Ok(())
+        );
+        assert_eq!(
+            move_recursively(&source.join("file"), &destination, 1, &progress),
+            Ok(())
+        );
+        assert!(destination.join("dir/sub/file").is_file());
+        assert!(destination.join("file").is_file());
+        assert!(!source.join("dir").exists() && !source.join("file").exists());
+        assert_eq!(
+            (progress.files_removed(), progress.bytes_removed()),
+            (0, 0),
+            "renaming within the same filesystem removes nothing"
+        );
+
+        fs::write(source.join("file"), [0; 10])?;
+        assert_eq!(
+            move_recursively(&source.join("file"), &destination, 1, &progress),
+            Err(1),
+            "existing entries aren't overwritten"
+        );
+        assert!(source.join("file").is_file());
+        assert_eq!(
+            move_recursively(&destination, &destination.join("dir"), 1, &progress),
+            Err(1),
+            "directories can't be moved into themselves"
+        );
+        assert_eq!(progress.errors(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn copies_retain_contents_and_symlinks() -> io::Result<()> {
+        let tmp = TempDir::new("dua-copy")?;
+        let root = tmp.path();
+        let source = root.join("source");
+        fs::create_dir_all(source.join("a/b"))?;
+        fs::write(source.join("a/b/file"), b"content")?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("b/file", source.join("a/link"))?;
+
+        let progress = DeletionProgress::default();
+        assert_eq!(copy_recursively(&source, &root.join("copy"), &progress), 0);
+        assert_eq!(fs::read(root.join("copy/a/b/file"))?, b"content");
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(root.join("copy/a/link"))?,
+            Path::new("b/file")
+        );
+        assert!(source.join("a/b/file").is_file(), "the source is kept");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn copies_retain_permissions_and_modification_times_of_directories() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = TempDir::new("dua-copy-metadata")?;
+        let root = tmp.path();
+        let source = root.join("source");
+        fs::create_dir_all(source.join("a/b"))?;
+        fs::write(source.join("a/b/file"), b"content")?;
+        let mtime =
+            std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        fs::File::open(source.join("a"))?.set_modified(mtime)?;
+        fs::set_permissions(source.join("a/b"), fs::Permissions::from_mode(0o750))?;
+
+        let progress = DeletionProgress::default();
+        assert_eq!(copy_recursively(&source, &root.join("copy"), &progress), 0);
+        assert_eq!(fs::metadata(root.join("copy/a"))?.modified()?, mtime);
+        assert_eq!(
+            fs::metadata(root.join("copy/a/b"))?.permissions().mode() & 0o777,
+            0o750
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn partial_copies_are_removed_and_nothing_is_deleted() -> io::Result<()> {
+        let tmp = TempDir::new("dua-copy-partial")?;
+        let root = tmp.path();
+        let source = root.join("source");
+        fs::create_dir_all(source.join("dir"))?;
+        fs::write(source.join("dir/file"), b"content")?;
+        // Sockets can't be copied.
+        let _socket = std::os::unix::net::UnixListener::bind(source.join("socket"))?;
+
+        let progress = Arc::new(DeletionProgress::default());
+        let target = root.join("copy");
+        assert_eq!(copy_then_delete(&source, &target, 1, &progress), Err(1));
+        assert!(
+            target.symlink_metadata().is_err(),
+            "the partial copy is gone"
+        );
+        assert!(source.join("dir/file").is_file(), "the source is kept");
+        Ok(())
+    }
+
+    #[test]
+    fn only_errors_of_renames_across_filesystems_lead_to_copies() {
+        let code = if cfg!(windows) { 17 } else { 18 };
+        assert!(crosses_devices(&io::Error::from_raw_os_error(code)));
+        assert!(!crosses_devices(&io::ErrorKind::NotFound.into()));
+    }
+}
@@ -0,0 +1,359 @@
+//! A compact binary format to store a [`Traversal`] on disk and load it later, possibly on another machine.
+//!
+//! All integers are LEB128 encoded. After the header, all nodes of the tree follow in pre-order,
+//! each one preceded by the position of its parent node, except for the root.
+use crate::traverse::{EntryData, EntryType, Traversal, Tree, TreeIndex};
+use anyhow::{bail, Context, Result};
+use petgraph::Direction;
+use std::{
+    convert::TryFrom,
+    ffi::OsString,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+const MAGIC: &[u8] = b"dua-snapshot";
+/// Increment whenever the layout of the data changes.
+const VERSION: u64 = 4;
+
+const FLAG_METADATA_IO_ERROR: u64 = 1;
+/// The most nodes to allocate room for up front, as the amount stored in a corrupt snapshot can't be trusted.
+const MAX_INITIAL_CAPACITY: usize = 1 << 16;
+
+impl Traversal {
+    /// Write this traversal in a compact binary format to `out`, to be loaded with [`Traversal::from_snapshot()`].
+    pub fn save_snapshot(&self, out: impl Write) -> io::Result<()> {
+        let mut out = BufWriter::new(out);
+        out.write_all(MAGIC)?;
+        write_int(&mut out, VERSION as u128)?;
+        write_int(&mut out, self.entries_traversed as u128)?;
+        write_int(&mut out, self.io_errors as u128)?;
+        write_option(&mut out, self.elapsed.map(|d| d.as_nanos()))?;
+        write_option(&mut out, self.total_bytes)?;
+        write_int(&mut out, self.tree.node_count() as u128)?;
+
+        let mut positions = 0_u64..;
+        let mut stack = vec![(self.root_index, None)];
+        while let Some((node_idx, parent_position)) = stack.pop() {
+            let position = positions.next().expect("unbounded");
+            if let Some(parent_position) = parent_position {
+                write_int(&mut out, parent_position as u128)?;
+            }
+            write_entry(&mut out, self.entry(node_idx))?;
+            // Children are returned newest first, and popped from the stack in reverse, retaining their order.
+            stack.extend(
+                self.tree
+                    .neighbors_directed(node_idx, Direction::Outgoing)
+                    .map(|child_idx| (child_idx, Some(position))),
+            );
+        }
+        out.flush()
+    }
+
+    /// Read a traversal previously written with [`Traversal::save_snapshot()`] from `input`.
+    ///
+    /// Note that `start` will be set to the current time, while `elapsed` is the duration of the original traversal.
+    pub fn from_snapshot(input: impl Read) -> Result<Traversal> {
+        let mut input = BufReader::new(input);
+        let mut magic = [0; MAGIC.len()];
+        input
+            .read_exact(&mut magic)
+            .ok()
+            .filter(|_| magic == MAGIC)
+            .with_context(|| "Not a dua snapshot")?;
+        let version = read_int(&mut input)?;
+        if version != VERSION as u128 {
+            bail!("Unsupported snapshot version {version}, expected {VERSION}")
+        }
+        let entries_traversed = read_int(&mut input)? as u64;
+        let io_errors = read_int(&mut input)? as u64;
+        let elapsed = read_option(&mut input)?.map(duration_from_nanos);
+        let total_bytes = read_option(&mut input)?;
+        let node_count = read_int(&mut input)? as usize;
+        if node_count == 0 {
+            bail!("Snapshot has no root node")
+        }
+
+        let capacity = node_count.min(MAX_INITIAL_CAPACITY);
+        let mut tree = Tree::with_capacity(capacity, capacity - 1);
+        let mut indices = Vec::<TreeIndex>::with_capacity(capacity);
+        let root_index = tree.add_node(read_entry(&mut input)?);
+        indices.push(root_index);
+        for _ in 1..node_count {
+            let parent_idx = *indices
+                .get(read_int(&mut input)? as usize)
+                .with_context(|| "Parent node must precede its children")?;
+            let node_idx = tree.add_node(read_entry(&mut input)?);
+            tree.add_edge(parent_idx, node_idx, ());
+            indices.push(node_idx);
+        }
+        if !input.fill_buf()?.is_empty() {
+            bail!("Unexpected data after the last node of the snapshot")
+        }
+
+        Ok(Traversal {
+            tree,
+            root_index,
+            entries_traversed,
+            start: Instant::now(),
+            elapsed,
+            io_errors,
+            total_bytes,
+        })
+    }
+
+    fn entry(&self, node_idx: TreeIndex) -> &EntryData {
+        crate::get_entry_or_panic(&self.tree, node_idx)
+    }
+}
+
+fn write_entry(out: &mut impl Write, entry: &EntryData) -> io::Result<()> {
+    let name = name_to_bytes(entry.name.clone().into_os_string());
+    write_int(out, name.len() as u128)?;
+    out.write_all(&name)?;
+    write_int(out, entry.size)?;
+    write_option(
+        out,
+        entry.mtime.map(|mtime| {
+            // Times before the epoch are rare enough to not be worth representing.
+            mtime
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        }),
+    )?;
+    write_int(out, entry.entry_count as u128)?;
+    write_int(out, entry.file_count as u128)?;
+    write_int(
+        out,
+        match entry.entry_type {
+            EntryType::File => 0,
+            EntryType::Directory => 1,
+            EntryType::Symlink => 2,
+            EntryType::Other => 3,
+            EntryType::Missing => 4,
+        },
+    )?;
+    let mut flags = 0;
+    if entry.metadata_io_error {
+        flags |= FLAG_METADATA_IO_ERROR;
+    }
+    write_int(out, flags as u128)
+}
+
+fn read_entry(input: &mut impl Read) -> Result<EntryData> {
+    let name_len =
+        u64::try_from(read_int(input)?).with_context(|| "Name in snapshot is too long")?;
+    // Only as much is allocated as there actually is to read.
+    let mut name = Vec::new();
+    input.by_ref().take(name_len).read_to_end(&mut name)?;
+    if name.len() as u64 != name_len {
+        bail!("Snapshot ends within the name of an entry")
+    }
+    let size = read_int(input)?;
+    let mtime =
+        read_option(input)?.map(|nanos| SystemTime::UNIX_EPOCH + duration_from_nanos(nanos));
+    let entry_count = read_int(input)? as u64;
+    let file_count = read_int(input)? as u64;
+    let entry_type = match read_int(input)? {
+        0 => EntryType::File,
+        1 => EntryType::Directory,
+        2 => EntryType::Symlink,
+        3 => EntryType::Other,
+        4 => EntryType::Missing,
+        other => bail!("Invalid entry type {other} in snapshot"),
+    };
+    let flags = read_int(input)? as u64;
+    Ok(EntryData {
+        name: name_from_bytes(name),
+        size,
+        mtime,
+        entry_count,
+        file_count,
+        entry_type,
+        metadata_io_error: flags & FLAG_METADATA_IO_ERROR != 0,
+    })
+}
+
+fn duration_from_nanos(nanos: u128) -> Duration {
+    Duration::new(
+        (nanos / 1_000_000_000) as u64,
+        (nanos % 1_000_000_000) as u32,
+    )
+}
+
+#[cfg(unix)]
+fn name_to_bytes(name: OsString) -> Vec<u8> {
+    use std::os::unix::ffi::OsStringExt;
+    name.into_vec()
+}
+
+#[cfg(not(unix))]
+fn name_to_bytes(name: OsString) -> Vec<u8> {
+    name.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+fn name_from_bytes(name: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(name).into()
+}
+
+/// Names that aren't valid UTF-8 can't be represented on this platform, and are converted lossily.
+#[cfg(not(unix))]
+fn name_from_bytes(name: Vec<u8>) -> PathBuf {
+    String::from_utf8_lossy(&name).into_owned().into()
+}
+
+fn write_option(out: &mut impl Write, value: Option<u128>) -> io::Result<()> {
+    match value {
+        Some(value) => {
+            out.write_all(&[1])?;
+            write_int(out, value)
+        }
+        None => out.write_all(&[0]),
+    }
+}
+
+fn read_option(input: &mut impl Read) -> Result<Option<u128>> {
+    let mut is_some = [0];
+    input.read_exact(&mut is_some)?;
+    Ok(match is_some[0] {
+        0 => None,
+        1 => Some(read_int(input)?),
+        _ => bail!("Invalid optional value in snapshot"),
+    })
+}
+
+fn write_int(out: &mut impl Write, mut value: u128) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return out.write_all(&[byte]);
+        }
+        out.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_int(input: &mut impl Read) -> Result<u128> {
+    let mut value = 0_u128;
+    for shift in (0..u128::BITS).step_by(7) {
+        let mut byte = [0];
+        input.read_exact(&mut byte)?;
+        value |= u128::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Integer in snapshot is too large")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ByteFormat, TraversalSorting, WalkOptions};
+
+    fn sample_02_traversal() -> Traversal {
+        Traversal::from_walk(
+            WalkOptions {
+                threads: 1,
+                byte_format: ByteFormat::Metric,
+                apparent_size: true,
+                count_hard_links: false,
+                sorting: TraversalSorting::AlphabeticalByFileName,
+                cross_filesystems: true,
+                ignore_dirs: Vec::new(),
+                exclude: Default::default(),
+                respect_ignore_files: false,
+            },
+            vec!["tests/fixtures/sample-02".into(), "does-not-exist".into()],
+            |_| Ok(false),
+        )
+        .unwrap()
+        .expect("not aborted")
+    }
+
+    /// Return all entries in pre-order, along with their depth, in the order the tree returns them.
+    fn entries(t: &Traversal) -> Vec<(usize, EntryData)> {
+        let mut out = Vec::new();
+        let mut stack = vec![(t.root_index, 0)];
+        while let Some((idx, depth)) = stack.pop() {
+            out.push((depth, t.entry(idx).clone()));
+            let mut children: Vec<_> = t
+                .tree
+                .neighbors_directed(idx, Direction::Outgoing)
+                .map(|idx| (idx, depth + 1))
+                .collect();
+            children.reverse();
+            stack.extend(children);
+        }
+        out
+    }
+
+    #[test]
+    fn snapshot_round_trip_retains_tree_order_and_statistics() -> Result<()> {
+        let traversal = sample_02_traversal();
+        let mut buf = Vec::new();
+        traversal.save_snapshot(&mut buf)?;
+        let loaded = Traversal::from_snapshot(buf.as_slice())?;
+
+        assert_eq!(entries(&loaded), entries(&traversal));
+        assert_eq!(loaded.entries_traversed, traversal.entries_traversed);
+        assert_eq!(loaded.io_errors, 1, "the input that doesn't exist");
+        assert_eq!(loaded.elapsed, traversal.elapsed);
+        assert_eq!(loaded.total_bytes, Some(1540));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_snapshots_are_rejected() -> Result<()> {
+        let mut buf = Vec::new();
+        sample_02_traversal().save_snapshot(&mut buf)?;
+
+        assert!(Traversal::from_snapshot(&b"not a snapshot"[..]).is_err());
+        assert!(Traversal::from_snapshot(&buf[..buf.len() - 1]).is_err());
+        buf.push(0);
+        assert!(Traversal::from_snapshot(buf.as_slice()).is_err());
+        let mut future = MAGIC.to_vec();
+        write_int(&mut future, VERSION as u128 + 1)?;
+        assert!(Traversal::from_snapshot(future.as_slice()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_sizes_in_snapshots_are_errors_instead_of_huge_allocations() -> Result<()> {
+        let header = || -> io::Result<Vec<u8>> {
+            let mut buf = MAGIC.to_vec();
+            for value in [VERSION as u128, 0, 0] {
+                write_int(&mut buf, value)?;
+            }
+            write_option(&mut buf, None)?;
+            write_option(&mut buf, None)?;
+            Ok(buf)
+        };
+
+        let mut many_nodes = header()?;
+        write_int(&mut many_nodes, u64::MAX as u128)?;
+        write_entry(&mut many_nodes, &EntryData::default())?;
+        assert!(Traversal::from_snapshot(many_nodes.as_slice()).is_err());
+
+        let mut long_name = header()?;
+        write_int(&mut long_name, 1)?;
+        write_int(&mut long_name, u64::MAX as u128)?;
+        long_name.extend_from_slice(b"short");
+        assert!(Traversal::from_snapshot(long_name.as_slice()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn integers_of_any_size_round_trip() -> Result<()> {
+        for value in [0, 1, 127, 128, 300, u64::MAX as u128, u128::MAX] {
+            let mut buf = Vec::new();
+            write_int(&mut buf, value)?;
+            assert_eq!(read_int(&mut buf.as_slice())?, value);
+        }
+        Ok(())
+    }
+}
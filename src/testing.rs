@@ -0,0 +1,38 @@
+//! Utilities for tests of both the library and the binary.
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// An empty directory that no other test or test run uses, which is removed with all of its contents when dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create a new directory in the temporary directory of the system, named after `prefix`.
+    pub fn new(prefix: &str) -> io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "{prefix}-{}-{}",
+            process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        // Left behind by a process that had the same id and didn't get to clean up.
+        fs::remove_dir_all(&path).ok();
+        fs::create_dir_all(&path)?;
+        Ok(TempDir { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.path).ok();
+    }
+}
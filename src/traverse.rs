@@ -1,4 +1,4 @@
-use crate::{crossdev, get_size_or_panic, InodeFilter, Throttle, WalkOptions};
+use crate::{crossdev, get_entry_or_panic, InodeFilter, Throttle, WalkOptions};
 use anyhow::Result;
 use filesize::PathExt;
 use petgraph::{graph::NodeIndex, stable_graph::StableGraph, Directed, Direction};
@@ -6,7 +6,7 @@ use std::{
     fs::Metadata,
     io,
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
 pub type TreeIndex = NodeIndex;
@@ -17,10 +17,55 @@ pub struct EntryData {
     pub name: PathBuf,
     /// The entry's size in bytes. If it's a directory, the size is the aggregated file size of all children
     pub size: u128,
+    /// The time of the entry's last modification, if known. If it's a directory, it's the most recent one of itself and all children
+    pub mtime: Option<SystemTime>,
+    /// The amount of entries below this one, recursively, which is 0 for files
+    pub entry_count: u64,
+    /// The amount of entries below this one that aren't directories, recursively, which is 0 for files
+    pub file_count: u64,
+    /// The kind of entry, as seen during the traversal
+    pub entry_type: EntryType,
     /// If set, the item meta-data could not be obtained
     pub metadata_io_error: bool,
 }
 
+/// The kind of an entry in the filesystem.
+#[derive(Eq, PartialEq, Debug, Default, Clone, Copy)]
+pub enum EntryType {
+    File,
+    Directory,
+    Symlink,
+    /// Anything else, like devices, sockets or pipes.
+    Other,
+    /// The entry didn't exist when it was last looked at.
+    #[default]
+    Missing,
+}
+
+impl EntryType {
+    pub fn is_dir(self) -> bool {
+        self == EntryType::Directory
+    }
+
+    pub fn exists(self) -> bool {
+        self != EntryType::Missing
+    }
+}
+
+impl From<std::fs::FileType> for EntryType {
+    fn from(file_type: std::fs::FileType) -> Self {
+        if file_type.is_dir() {
+            EntryType::Directory
+        } else if file_type.is_symlink() {
+            EntryType::Symlink
+        } else if file_type.is_file() {
+            EntryType::File
+        } else {
+            EntryType::Other
+        }
+    }
+}
+
 /// The result of the previous filesystem traversal
 #[derive(Debug)]
 pub struct Traversal {
@@ -46,37 +91,33 @@ impl Traversal {
         input: Vec<PathBuf>,
         mut update: impl FnMut(&mut Traversal) -> Result<bool>,
     ) -> Result<Option<Traversal>> {
-        fn set_size_or_panic(tree: &mut Tree, node_idx: TreeIndex, current_size_at_depth: u128) {
-            tree.node_weight_mut(node_idx)
-                .expect("node for parent index we just retrieved")
-                .size = current_size_at_depth;
+        fn set_size_or_panic(
+            tree: &mut Tree,
+            node_idx: TreeIndex,
+            current_size_at_depth: Accumulated,
+        ) {
+            let entry = tree
+                .node_weight_mut(node_idx)
+                .expect("node for parent index we just retrieved");
+            entry.size = current_size_at_depth.size;
+            entry.entry_count = current_size_at_depth.entry_count;
+            entry.file_count = current_size_at_depth.file_count;
+            entry.mtime = entry.mtime.max(current_size_at_depth.mtime);
         }
         fn parent_or_panic(tree: &mut Tree, parent_node_idx: TreeIndex) -> TreeIndex {
             tree.neighbors_directed(parent_node_idx, Direction::Incoming)
                 .next()
                 .expect("every node in the iteration has a parent")
         }
-        fn pop_or_panic(v: &mut Vec<u128>) -> u128 {
+        fn pop_or_panic(v: &mut Vec<Accumulated>) -> Accumulated {
             v.pop().expect("sizes per level to be in sync with graph")
         }
 
-        let mut t = {
-            let mut tree = Tree::new();
-            let root_index = tree.add_node(EntryData::default());
-            Traversal {
-                tree,
-                root_index,
-                entries_traversed: 0,
-                start: std::time::Instant::now(),
-                elapsed: None,
-                io_errors: 0,
-                total_bytes: None,
-            }
-        };
+        let mut t = Traversal::empty(std::time::Instant::now());
 
         let (mut previous_node_idx, mut parent_node_idx) = (t.root_index, t.root_index);
         let mut sizes_per_depth_level = Vec::new();
-        let mut current_size_at_depth: u128 = 0;
+        let mut current_size_at_depth = Accumulated::default();
         let mut previous_depth = 0;
         let mut inodes = InodeFilter::default();
 
@@ -144,11 +185,18 @@ impl Traversal {
                             }
                             None => 0, // a directory
                         } as u128;
+                        data.size = file_size;
+                        data.entry_type = entry.file_type.into();
+                        data.mtime = match &entry.client_state {
+                            Some(Ok(m)) => m.modified().ok(),
+                            _ => None,
+                        };
+                        let entry_at_depth = Accumulated::of_entry(&data);
 
                         match (entry.depth, previous_depth) {
                             (n, p) if n > p => {
                                 sizes_per_depth_level.push(current_size_at_depth);
-                                current_size_at_depth = file_size;
+                                current_size_at_depth = entry_at_depth;
                                 parent_node_idx = previous_node_idx;
                             }
                             (n, p) if n < p => {
@@ -158,11 +206,11 @@ impl Traversal {
                                         parent_node_idx,
                                         current_size_at_depth,
                                     );
-                                    current_size_at_depth +=
-                                        pop_or_panic(&mut sizes_per_depth_level);
+                                    current_size_at_depth
+                                        .add(pop_or_panic(&mut sizes_per_depth_level));
                                     parent_node_idx = parent_or_panic(&mut t.tree, parent_node_idx);
                                 }
-                                current_size_at_depth += file_size;
+                                current_size_at_depth.add(entry_at_depth);
                                 set_size_or_panic(
                                     &mut t.tree,
                                     parent_node_idx,
@@ -170,11 +218,10 @@ impl Traversal {
                                 );
                             }
                             _ => {
-                                current_size_at_depth += file_size;
+                                current_size_at_depth.add(entry_at_depth);
                             }
                         };
 
-                        data.size = file_size;
                         let entry_index = t.tree.add_node(data);
 
                         t.tree.add_edge(parent_node_idx, entry_index, ());
@@ -199,24 +246,74 @@ impl Traversal {
         }
 
         sizes_per_depth_level.push(current_size_at_depth);
-        current_size_at_depth = 0;
+        current_size_at_depth = Accumulated::default();
         for _ in 0..previous_depth {
-            current_size_at_depth += pop_or_panic(&mut sizes_per_depth_level);
+            current_size_at_depth.add(pop_or_panic(&mut sizes_per_depth_level));
             set_size_or_panic(&mut t.tree, parent_node_idx, current_size_at_depth);
             parent_node_idx = parent_or_panic(&mut t.tree, parent_node_idx);
         }
-        let root_size = t.recompute_root_size();
-        set_size_or_panic(&mut t.tree, t.root_index, root_size);
-        t.total_bytes = Some(root_size);
+        let root = t.recompute_root();
+        set_size_or_panic(&mut t.tree, t.root_index, root);
+        t.total_bytes = Some(root.size);
 
         t.elapsed = Some(t.start.elapsed());
         Ok(Some(t))
     }
 
-    fn recompute_root_size(&self) -> u128 {
-        self.tree
+    /// Return a traversal without any entries besides the root, started at `start`.
+    pub(crate) fn empty(start: std::time::Instant) -> Self {
+        let mut tree = Tree::new();
+        let root_index = tree.add_node(EntryData {
+            entry_type: EntryType::Directory,
+            ..Default::default()
+        });
+        Traversal {
+            tree,
+            root_index,
+            entries_traversed: 0,
+            start,
+            elapsed: None,
+            io_errors: 0,
+            total_bytes: None,
+        }
+    }
+
+    fn recompute_root(&self) -> Accumulated {
+        let mut root = Accumulated::default();
+        for idx in self
+            .tree
             .neighbors_directed(self.root_index, Direction::Outgoing)
-            .map(|idx| get_size_or_panic(&self.tree, idx))
-            .sum()
+        {
+            root.add(Accumulated::of_entry(get_entry_or_panic(&self.tree, idx)));
+        }
+        root
+    }
+}
+
+/// The total size and amount of a set of entries and all of their children, along with the most recent modification time.
+#[derive(Default, Clone, Copy)]
+struct Accumulated {
+    size: u128,
+    entry_count: u64,
+    file_count: u64,
+    mtime: Option<SystemTime>,
+}
+
+impl Accumulated {
+    /// The contribution of `entry` and all of its children.
+    fn of_entry(entry: &EntryData) -> Self {
+        Accumulated {
+            size: entry.size,
+            entry_count: 1 + entry.entry_count,
+            file_count: u64::from(!entry.entry_type.is_dir()) + entry.file_count,
+            mtime: entry.mtime,
+        }
+    }
+
+    fn add(&mut self, other: Accumulated) {
+        self.size += other.size;
+        self.entry_count += other.entry_count;
+        self.file_count += other.file_count;
+        self.mtime = self.mtime.max(other.mtime);
     }
 }